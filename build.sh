@@ -14,5 +14,6 @@ trap clean EXIT
 
 # This will be done by build.rs of `server` anyway, but this way, we get nicer output
 cargo build --manifest-path generator/Cargo.toml --release
+cargo run --manifest-path generator/Cargo.toml --release -- vendor
 
 GENERATOR_BUILD_DIRECTORY="${build_dir}" cargo build --manifest-path server/Cargo.toml --release --target x86_64-unknown-linux-musl
@@ -9,9 +9,13 @@ build() {
 }
 
 detect_changes() {
-    inotifywait generator/src static/ blog/ --event modify,move,create,delete,attrib
+    inotifywait generator/src static/ blog/ site.toml --event modify,move,create,delete,attrib
 }
 
+# Without network access, the relaxed build leaves out scripts that are not
+# vendored yet
+cargo run --manifest-path ./generator/Cargo.toml vendor || echo "warning: could not download vendored scripts"
+
 build
 while detect_changes ; do
   build
@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+pub const LOCKFILE: &str = "links.lock";
+
+#[derive(PartialEq, Eq)]
+pub enum Mode {
+    Online,
+    Offline,
+}
+
+impl TryFrom<String> for Mode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(match value.as_str() {
+            "online" => Self::Online,
+            "offline" => Self::Offline,
+            _ => return Err(format!("unknown link check mode {value}")),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Broken,
+    Unreachable,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    url: String,
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    checked: Option<time::OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    archived: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Lockfile {
+    #[serde(default, rename = "link")]
+    links: Vec<Entry>,
+}
+
+impl Lockfile {
+    fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        toml::from_str(&std::fs::read_to_string(path).unwrap())
+            .unwrap_or_else(|e| panic!("invalid lockfile {path:?}: {e}"))
+    }
+
+    fn write(&self, path: &Path) {
+        std::fs::write(path, toml::to_string(self).unwrap()).unwrap();
+    }
+}
+
+struct Checker {
+    agent: ureq::Agent,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(15))
+                .build(),
+        }
+    }
+
+    fn check(&self, url: &str) -> (Status, Option<u16>) {
+        // some servers do not implement HEAD, so fall back to GET
+        let response = match self.agent.head(url).call() {
+            Err(ureq::Error::Status(405, _)) => self.agent.get(url).call(),
+            response => response,
+        };
+
+        match response {
+            Ok(response) => (Status::Ok, Some(response.status())),
+            Err(ureq::Error::Status(code, _)) => (Status::Broken, Some(code)),
+            Err(ureq::Error::Transport(_)) => (Status::Unreachable, None),
+        }
+    }
+
+    /// Looks up the most recent snapshot on the Internet Archive.
+    fn archived(&self, url: &str) -> Option<String> {
+        let response = self
+            .agent
+            .get("https://archive.org/wayback/available")
+            .query("url", url)
+            .call()
+            .ok()?
+            .into_string()
+            .ok()?;
+
+        let response: serde_json::Value = serde_json::from_str(&response).ok()?;
+        response["archived_snapshots"]["closest"]["url"]
+            .as_str()
+            .map(ToOwned::to_owned)
+    }
+}
+
+/// Compares the external links of the rendered site with the lockfile. In
+/// online mode, all links are checked and the lockfile is updated. In offline
+/// mode, the lockfile is only read and links that are missing from it fail the
+/// check, as nobody reviewed them yet.
+pub fn run(mode: &Mode, external_links: &BTreeMap<String, BTreeSet<String>>, input_path: &Path) {
+    let lockfile_path = input_path.join(LOCKFILE);
+    if *mode == Mode::Offline && !lockfile_path.exists() {
+        panic!(
+            "{LOCKFILE} does not exist yet, run the online check once to record the external links"
+        );
+    }
+    let lockfile = Lockfile::load(&lockfile_path);
+
+    let mut locked: BTreeMap<String, Entry> = lockfile
+        .links
+        .into_iter()
+        .map(|entry| (entry.url.clone(), entry))
+        .collect();
+
+    for url in locked.keys() {
+        if !external_links.contains_key(url) {
+            println!("stale: {url} is not linked anymore");
+        }
+    }
+
+    match mode {
+        Mode::Offline => {
+            let mut unreviewed = vec![];
+            for (url, pages) in external_links {
+                match locked.get(url) {
+                    None => unreviewed.push(format!(
+                        "{url} (linked from {})",
+                        pages.iter().cloned().collect::<Vec<String>>().join(", ")
+                    )),
+                    Some(entry) => {
+                        if entry.status != Some(Status::Ok) && entry.archived.is_none() {
+                            println!(
+                                "warning: {url} was {} on last check and has no archived version",
+                                match entry.status {
+                                    Some(Status::Broken) => "broken",
+                                    Some(Status::Unreachable) => "unreachable",
+                                    Some(Status::Ok) | None => "not checked",
+                                }
+                            );
+                        }
+                    }
+                }
+            }
+
+            if !unreviewed.is_empty() {
+                panic!(
+                    "external links missing from {LOCKFILE}, run the online check:\n{}",
+                    unreviewed.join("\n")
+                );
+            }
+        }
+        Mode::Online => {
+            let checker = Checker::new();
+            let now = time::OffsetDateTime::now_utc()
+                .replace_nanosecond(0)
+                .unwrap();
+
+            let mut broken = vec![];
+
+            locked.retain(|url, _entry| external_links.contains_key(url));
+
+            for url in external_links.keys() {
+                let (status, code) = checker.check(url);
+                println!(
+                    "{url}: {status:?}{}",
+                    code.map(|code| format!(" ({code})")).unwrap_or_default()
+                );
+
+                let entry = locked.entry(url.clone()).or_insert_with(|| Entry {
+                    url: url.clone(),
+                    checked: None,
+                    status: None,
+                    code: None,
+                    archived: None,
+                });
+
+                entry.checked = Some(now);
+                entry.status = Some(status);
+                entry.code = code;
+
+                if status != Status::Ok && entry.archived.is_none() {
+                    entry.archived = checker.archived(url);
+                    if entry.archived.is_none() {
+                        broken.push(url.clone());
+                    }
+                }
+            }
+
+            Lockfile {
+                links: locked.into_values().collect(),
+            }
+            .write(&lockfile_path);
+
+            if !broken.is_empty() {
+                panic!(
+                    "broken external links without archived version:\n{}",
+                    broken.join("\n")
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use maud::{html, Markup, PreEscaped};
+
+use super::config::SiteConfig;
+use super::icon::IconRegistry;
+use super::markdown::{self, Heading, Headings};
+use super::meta::Metadata;
+use super::render::Renderer;
+use super::{frame, Blogpost, CheckMode, FileType, Frontmatter, Page};
+
+/// The reading speed the reading time is estimated with
+const WORDS_PER_MINUTE: usize = 200;
+
+/// The estimated reading time in minutes
+pub fn reading_time(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE).max(1)
+}
+
+fn post_table(posts: &[&Blogpost], config: &SiteConfig) -> Markup {
+    html!(
+        div .postlist {
+            table {
+                tbody {
+                    @for blog_post in posts {
+                        tr {
+                            td {
+                                a href=(config.blog.link(&blog_post.html_filename)) {
+                                    (blog_post.frontmatter.title)
+                                }
+                            }
+                            td {
+                                (blog_post.frontmatter.timestamp.date())
+                            }
+                            td .reading-time {
+                                (format!("{} min", reading_time(blog_post.words)))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+fn toc_list(headings: &[Heading]) -> Markup {
+    // every heading contains all following headings of a lower level
+    let mut items = vec![];
+    let mut i = 0;
+    while i < headings.len() {
+        let level = headings[i].level;
+        let end = headings[i + 1..]
+            .iter()
+            .position(|heading| heading.level <= level)
+            .map_or(headings.len(), |position| i + 1 + position);
+        items.push((&headings[i], &headings[i + 1..end]));
+        i = end;
+    }
+
+    html!(
+        ol {
+            @for (heading, children) in items {
+                li {
+                    a href=(format!("#{}", heading.id)) { (heading.text) }
+                    @if !children.is_empty() {
+                        (toc_list(children))
+                    }
+                }
+            }
+        }
+    )
+}
+
+pub fn table_of_contents(headings: &Headings) -> Markup {
+    html!(
+        @if !headings.0.is_empty() {
+            nav .toc aria-label="table of contents" {
+                details open {
+                    summary { "Contents" }
+                    (toc_list(&headings.0))
+                }
+            }
+        }
+    )
+}
+
+/// When the post was last updated, with the list of changes if there are any,
+/// newest first.
+pub fn updates(frontmatter: &Frontmatter) -> Markup {
+    let mut changes: Vec<_> = frontmatter.changes.iter().collect();
+    changes.sort_by_key(|change| std::cmp::Reverse(change.date));
+
+    html!(
+        @if let Some(updated) = frontmatter.updated {
+            @let last_updated = html!(
+                "Last updated "
+                time datetime=(updated.date()) { (updated.date()) }
+            );
+            div .updated {
+                @if changes.is_empty() {
+                    p { (last_updated) }
+                } @else {
+                    details {
+                        summary { (last_updated) }
+                        ol .changes {
+                            @for change in changes {
+                                li {
+                                    time datetime=(change.date.date()) { (change.date.date()) }
+                                    " "
+                                    (change.description)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+/// The maximum number of related posts shown below a post
+const RELATED_POSTS: usize = 3;
+
+/// Links to the chronologically previous and next post. `blog_posts` are
+/// sorted newest first.
+pub fn post_navigation(blog_posts: &[Blogpost], position: usize, config: &SiteConfig) -> Markup {
+    let previous = blog_posts.get(position + 1);
+    let next = position.checked_sub(1).and_then(|i| blog_posts.get(i));
+
+    html!(
+        nav .post-navigation aria-label="previous and next post" {
+            @if let Some(previous) = previous {
+                a .previous href=(config.blog.link(&previous.html_filename)) rel="prev" {
+                    span .label { "← Previous post" }
+                    span .title { (previous.frontmatter.title) }
+                }
+            }
+            @if let Some(next) = next {
+                a .next href=(config.blog.link(&next.html_filename)) rel="next" {
+                    span .label { "Next post →" }
+                    span .title { (next.frontmatter.title) }
+                }
+            }
+        }
+    )
+}
+
+/// Other posts ranked by the number of tags they share with the post, newer
+/// posts first on ties.
+fn related(blog_posts: &[Blogpost], position: usize) -> Vec<&Blogpost> {
+    let tags: BTreeSet<&str> = blog_posts[position]
+        .frontmatter
+        .tags
+        .iter()
+        .map(|tag| tag.0.as_str())
+        .collect();
+
+    let mut scored: Vec<(usize, &Blogpost)> = blog_posts
+        .iter()
+        .enumerate()
+        .filter(|(i, _blog_post)| *i != position)
+        .map(|(_i, blog_post)| {
+            let shared = blog_post
+                .frontmatter
+                .tags
+                .iter()
+                .filter(|tag| tags.contains(tag.0.as_str()))
+                .count();
+            (shared, blog_post)
+        })
+        .filter(|(shared, _blog_post)| *shared > 0)
+        .collect();
+
+    // the sort is stable, so ties keep the order of `blog_posts`
+    scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    scored
+        .into_iter()
+        .take(RELATED_POSTS)
+        .map(|(_shared, blog_post)| blog_post)
+        .collect()
+}
+
+pub fn related_posts(blog_posts: &[Blogpost], position: usize, config: &SiteConfig) -> Markup {
+    let related = related(blog_posts, position);
+
+    html!(
+        @if !related.is_empty() {
+            aside .related-posts {
+                h2 { "Related posts" }
+                ul {
+                    @for blog_post in related {
+                        li {
+                            a href=(config.blog.link(&blog_post.html_filename)) {
+                                (blog_post.frontmatter.title)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+pub struct Series<'a> {
+    pub name: &'a str,
+    /// Ordered by part
+    pub parts: Vec<&'a Blogpost>,
+}
+
+impl Series<'_> {
+    /// The path of the index page of the series, relative to the blog
+    /// directory
+    fn index_page(&self) -> String {
+        format!("series/{}.html", markdown::slug(self.name))
+    }
+}
+
+/// Collects all series, keyed by name, and checks that the parts of each are
+/// unique and numbered without gaps, starting at 1.
+pub fn series<'a>(
+    blog_posts: &'a [Blogpost],
+    check_mode: &CheckMode,
+) -> BTreeMap<&'a str, Series<'a>> {
+    let mut series: BTreeMap<&str, Series> = BTreeMap::new();
+    let mut errors = vec![];
+
+    for blog_post in blog_posts {
+        let frontmatter = &blog_post.frontmatter;
+        match (&frontmatter.series, frontmatter.series_part) {
+            (Some(name), Some(_part)) => series
+                .entry(name)
+                .or_insert_with(|| Series {
+                    name,
+                    parts: vec![],
+                })
+                .parts
+                .push(blog_post),
+            (None, None) => (),
+            _ => errors.push(format!(
+                "{:?}: series and series_part have to be set together",
+                blog_post.path
+            )),
+        }
+    }
+
+    let mut index_pages = BTreeSet::new();
+    for series in series.values_mut() {
+        series
+            .parts
+            .sort_by_key(|blog_post| blog_post.frontmatter.series_part);
+
+        for (expected, blog_post) in (1..).zip(&series.parts) {
+            let part = blog_post.frontmatter.series_part.unwrap();
+            if part != expected {
+                errors.push(format!(
+                    "{:?}: series {:?} has part {part} where part {expected} was expected, parts have to be unique and without gaps",
+                    blog_post.path, series.name
+                ));
+                break;
+            }
+        }
+
+        if !index_pages.insert(series.index_page()) {
+            errors.push(format!(
+                "series {:?} has the same index page as another series",
+                series.name
+            ));
+        }
+    }
+
+    if !errors.is_empty() {
+        check_mode.report(format!("invalid series:\n{}", errors.join("\n")));
+    }
+
+    series
+}
+
+/// The box at the top of each part of a series, listing all parts.
+pub fn series_navigation(
+    series: &BTreeMap<&str, Series>,
+    blog_post: &Blogpost,
+    config: &SiteConfig,
+) -> Markup {
+    let Some(series) = blog_post
+        .frontmatter
+        .series
+        .as_deref()
+        .and_then(|name| series.get(name))
+    else {
+        return html!();
+    };
+
+    html!(
+        aside .series aria-label="series" {
+            p {
+                (format!("This post is part {} of the series ", blog_post.frontmatter.series_part.unwrap()))
+                a href=(config.blog.link(&series.index_page())) { (series.name) }
+                ":"
+            }
+            ol {
+                @for part in &series.parts {
+                    li {
+                        @if std::ptr::eq(*part, blog_post) {
+                            span aria-current="page" { (part.frontmatter.title) }
+                        } @else {
+                            a href=(config.blog.link(&part.html_filename)) { (part.frontmatter.title) }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+/// Renders an index page for every series.
+pub fn render_series(
+    output_base_path: &Path,
+    series: &BTreeMap<&str, Series>,
+    renderer: &Renderer,
+    config: &SiteConfig,
+    icons: &IconRegistry,
+) -> Vec<Page> {
+    let output = Output {
+        output_base_path,
+        renderer,
+        config,
+        icons,
+    };
+
+    series
+        .values()
+        .map(|series| {
+            let inner = html!(
+                div .series-index {
+                    h1 { (series.name) }
+                    p { (format!("A series in {} parts.", series.parts.len())) }
+                    ol {
+                        @for part in &series.parts {
+                            li {
+                                a href=(config.blog.link(&part.html_filename)) { (part.frontmatter.title) }
+                                p .summary { (PreEscaped(&part.frontmatter.summary)) }
+                            }
+                        }
+                    }
+                }
+            );
+            output.render(
+                &series.index_page(),
+                &format!("Series: {}", series.name),
+                inner,
+            )
+        })
+        .collect()
+}
+
+/// The path of a page of the index, relative to the blog directory. The first
+/// page is the index itself.
+fn index_page(number: usize) -> String {
+    match number {
+        1 => "index.html".to_owned(),
+        n => format!("page/{n}.html"),
+    }
+}
+
+fn month_directory(year: i32, month: time::Month) -> String {
+    format!("{year}/{:02}", u8::from(month))
+}
+
+/// Posts grouped by year and month
+type Archive<'a> = BTreeMap<i32, BTreeMap<u8, Vec<&'a Blogpost>>>;
+
+fn archive(blog_posts: &[Blogpost]) -> Archive<'_> {
+    let mut archive = Archive::new();
+    for blog_post in blog_posts {
+        let date = blog_post.frontmatter.timestamp.date();
+        archive
+            .entry(date.year())
+            .or_default()
+            .entry(u8::from(date.month()))
+            .or_default()
+            .push(blog_post);
+    }
+    archive
+}
+
+fn archive_links(archive: &Archive<'_>, config: &SiteConfig) -> Markup {
+    html!(
+        nav .archive aria-label="archive" {
+            h2 { "Archive" }
+            ul {
+                @for (year, months) in archive.iter().rev() {
+                    li {
+                        a href=(config.blog.link(&format!("{year}/index.html"))) { (year) }
+                        (format!(" ({})", months.values().map(Vec::len).sum::<usize>()))
+                    }
+                }
+            }
+        }
+    )
+}
+
+struct Output<'a> {
+    output_base_path: &'a Path,
+    renderer: &'a Renderer,
+    config: &'a SiteConfig,
+    icons: &'a IconRegistry,
+}
+
+impl Output<'_> {
+    /// Renders a page into the blog directory, `path` is relative to it.
+    fn render(&self, path: &str, title: &str, inner: Markup) -> Page {
+        let page_path = Path::new(&self.config.blog.path)
+            .join(path)
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let output_path = self.output_base_path.join(&page_path);
+        std::fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+
+        self.renderer.render_into(
+            frame(
+                &Metadata::page(title, &page_path, self.config),
+                inner,
+                self.config,
+                self.icons,
+            ),
+            &output_path,
+        );
+
+        Page {
+            path: page_path,
+            filetype: FileType::Html,
+        }
+    }
+}
+
+/// Renders the paginated index and the archive pages for every year and
+/// month. `blog_posts` have to be sorted, newest first.
+pub fn render_index(
+    output_base_path: &Path,
+    blog_posts: &[Blogpost],
+    renderer: &Renderer,
+    config: &SiteConfig,
+    icons: &IconRegistry,
+) -> Vec<Page> {
+    let output = Output {
+        output_base_path,
+        renderer,
+        config,
+        icons,
+    };
+
+    let mut pages = vec![];
+    let archive = archive(blog_posts);
+
+    let chunks: Vec<Vec<&Blogpost>> = blog_posts
+        .chunks(config.blog.posts_per_page)
+        .map(|chunk| chunk.iter().collect())
+        .collect();
+    let page_count = chunks.len().max(1);
+
+    for number in 1..=page_count {
+        let posts = chunks
+            .get(number - 1)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let title = match number {
+            1 => "Blog posts".to_owned(),
+            n => format!("Blog posts, page {n}"),
+        };
+
+        let inner = html!(
+            div .blog-index {
+                h1 .visually-hidden { (title) }
+                (post_table(posts, config))
+            }
+            @if page_count > 1 {
+                nav .pagination aria-label="pagination" {
+                    @if number > 1 {
+                        a .newer href=(config.blog.link(&index_page(number - 1))) { "← Newer posts" }
+                    }
+                    span .position { (format!("Page {number} of {page_count}")) }
+                    @if number < page_count {
+                        a .older href=(config.blog.link(&index_page(number + 1))) { "Older posts →" }
+                    }
+                }
+            }
+            (archive_links(&archive, config))
+        );
+        pages.push(output.render(&index_page(number), &title, inner));
+    }
+
+    for (year, months) in &archive {
+        let inner = html!(
+            div .archive-year {
+                h1 { (format!("Posts from {year}")) }
+                @for (month, posts) in months.iter().rev() {
+                    @let month = time::Month::try_from(*month).unwrap();
+                    h2 {
+                        a href=(config.blog.link(&format!("{}/index.html", month_directory(*year, month)))) {
+                            (month)
+                        }
+                    }
+                    (post_table(posts, config))
+                }
+            }
+            (archive_links(&archive, config))
+        );
+        pages.push(output.render(
+            &format!("{year}/index.html"),
+            &format!("Blog posts from {year}"),
+            inner,
+        ));
+
+        for (month, posts) in months {
+            let month = time::Month::try_from(*month).unwrap();
+            let inner = html!(
+                div .archive-month {
+                    h1 { (format!("Posts from {month} {year}")) }
+                    (post_table(posts, config))
+                    a href=(config.blog.link(&format!("{year}/index.html"))) {
+                        (format!("All posts from {year}"))
+                    }
+                }
+            );
+            pages.push(output.render(
+                &format!("{}/index.html", month_directory(*year, month)),
+                &format!("Blog posts from {month} {year}"),
+                inner,
+            ));
+        }
+    }
+
+    pages
+}
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+pub const CONFIG_FILE: &str = "site.toml";
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IconMode {
+    #[default]
+    File,
+    Inline,
+    Sprite,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct IconConfig {
+    #[serde(default)]
+    pub mode: IconMode,
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// The search page queries a JSON index in the browser
+    #[default]
+    Client,
+    /// The search page queries the `/search` endpoint of the server via htmx
+    Server,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SearchConfig {
+    #[serde(default)]
+    pub mode: SearchMode,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GitConfig {
+    /// Link to the history of the source file of a page, `{path}` is replaced
+    /// with the path of the file in the repository
+    #[serde(default)]
+    pub history_url: Option<String>,
+    /// Warn about posts whose `date` is more than this many days away from
+    /// the first commit of their file
+    #[serde(default)]
+    pub date_tolerance: Option<u32>,
+}
+
+/// Maps the color tokens of the theme to CSS color values.
+pub type Colors = BTreeMap<String, String>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Palette {
+    pub light: Colors,
+    pub dark: Colors,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The name of the palette that is used for the site
+    pub palette: String,
+    pub palettes: BTreeMap<String, Palette>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CssConfig {
+    /// Stylesheets in `static`, bundled in this order after the theme
+    pub stylesheets: Vec<String>,
+    #[serde(default = "enabled")]
+    pub minify: bool,
+    /// Drop rules whose selectors do not match anything in the generated pages
+    #[serde(default = "enabled")]
+    pub remove_unused: bool,
+    /// Inline the rules each page needs into its `head` and load the full
+    /// stylesheet asynchronously
+    #[serde(default)]
+    pub inline_critical: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HighlightConfig {
+    /// Names of syntect themes
+    pub light: String,
+    pub dark: String,
+    /// A directory with additional `.sublime-syntax` definitions
+    #[serde(default)]
+    pub syntaxes: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HtmlConfig {
+    #[serde(default = "enabled")]
+    pub minify: bool,
+}
+
+/// A front-end script that is vendored into `static/vendor` and served from
+/// the site itself.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Where the pinned version is downloaded from
+    pub url: String,
+    pub file: String,
+    /// Subresource Integrity hash, e.g. `sha384-...`
+    pub integrity: String,
+}
+
+impl Script {
+    pub fn output_path(&self) -> String {
+        format!("/vendor/{}", self.file)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NavigationEntry {
+    pub name: String,
+    pub link: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LandingConfig {
+    pub heading: String,
+    pub introduction: String,
+    pub image: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlogConfig {
+    /// Where posts are published, relative to the root of the site
+    pub path: String,
+    /// The number of posts on each page of the index
+    #[serde(default = "posts_per_page")]
+    pub posts_per_page: usize,
+    /// Posts with at least this many headings get a table of contents, unless
+    /// they set `toc` in their frontmatter
+    #[serde(default = "toc_threshold")]
+    pub toc_threshold: usize,
+}
+
+fn posts_per_page() -> usize {
+    10
+}
+
+fn toc_threshold() -> usize {
+    5
+}
+
+impl BlogConfig {
+    /// The absolute URL path of a file in the blog.
+    pub fn link(&self, filename: &str) -> String {
+        format!("/{}/{filename}", self.path)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Social {
+    pub name: String,
+    pub link: String,
+    /// Name of the icon, see `IconRef`
+    pub icon: String,
+    /// Defaults to "Me on <name>"
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FooterConfig {
+    #[serde(default)]
+    pub socials: Vec<Social>,
+    /// Show the badges of certifications
+    #[serde(default = "enabled")]
+    pub badges: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteConfig {
+    pub title: String,
+    pub author: String,
+    /// The URL the site is published at, without a trailing slash
+    pub base_url: String,
+    pub language: String,
+    /// Description of pages that do not have their own
+    pub description: String,
+    /// Shown in link previews of pages that do not have their own image
+    pub image: String,
+    pub navigation: Vec<NavigationEntry>,
+    pub landing: LandingConfig,
+    pub blog: BlogConfig,
+    pub footer: FooterConfig,
+    #[serde(default)]
+    pub icons: IconConfig,
+    pub theme: ThemeConfig,
+    pub css: CssConfig,
+    pub highlighting: HighlightConfig,
+    pub html: HtmlConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub scripts: BTreeMap<String, Script>,
+}
+
+impl SiteConfig {
+    pub fn load(input_path: &Path) -> Self {
+        let path = input_path.join(CONFIG_FILE);
+        let config: Self = toml::from_str(&std::fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|e| panic!("invalid configuration {path:?}: {e}"));
+
+        if !(config.base_url.starts_with("https://") || config.base_url.starts_with("http://"))
+            || config.base_url.ends_with('/')
+        {
+            panic!(
+                "invalid configuration {path:?}: base_url {:?} must be an absolute URL without a trailing slash",
+                config.base_url
+            );
+        }
+
+        if config.blog.path.is_empty()
+            || !Path::new(&config.blog.path)
+                .components()
+                .all(|component| matches!(component, std::path::Component::Normal(_)))
+        {
+            panic!(
+                "invalid configuration {path:?}: blog path {:?} must be a relative path",
+                config.blog.path
+            );
+        }
+
+        if config.blog.posts_per_page == 0 {
+            panic!("invalid configuration {path:?}: posts_per_page must not be 0");
+        }
+
+        if config.search.mode == SearchMode::Server && !config.htmx() {
+            panic!("invalid configuration {path:?}: server-side search requires htmx");
+        }
+
+        if let Some(history_url) = &config.git.history_url {
+            if !history_url.contains("{path}") {
+                panic!(
+                    "invalid configuration {path:?}: history_url {history_url:?} must contain {{path}}"
+                );
+            }
+        }
+
+        config
+    }
+
+    /// The absolute URL of a path on the site. External URLs are returned
+    /// unchanged.
+    pub fn url(&self, path: &str) -> String {
+        if path.contains("://") {
+            return path.to_owned();
+        }
+        format!("{}/{}", self.base_url, path.trim_start_matches('/'))
+    }
+
+    /// Whether pages use htmx to boost navigation.
+    pub fn htmx(&self) -> bool {
+        self.scripts
+            .get("htmx")
+            .is_some_and(|script| script.enabled)
+    }
+}
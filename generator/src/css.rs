@@ -0,0 +1,586 @@
+// Bundles all stylesheets into a single file. The parser only understands as
+// much CSS as is needed to inline imports and to drop unused rules, everything
+// else is passed through.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use super::config::CssConfig;
+use super::html::{self, Token};
+use super::{FileType, Page};
+
+/// Pages reference this stylesheet, it is replaced by the bundle once all
+/// pages are rendered.
+pub const PLACEHOLDER: &str = "/STYLESHEET";
+
+/// At-rules that contain regular rules.
+const GROUPING_AT_RULES: &[&str] = &["media", "supports", "layer", "container", "scope"];
+
+enum Node {
+    /// A declaration or an at-rule without a block, e.g. `@charset`
+    Statement(String),
+    /// A style rule or a grouping at-rule like `@media`
+    Block {
+        prelude: String,
+        children: Vec<Node>,
+    },
+    /// An at-rule whose block is kept verbatim, e.g. `@keyframes`
+    Raw { prelude: String, block: String },
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Copies a string literal including its quotes.
+    fn string(&mut self, quote: char, output: &mut String) {
+        output.push(quote);
+        while let Some(c) = self.chars.next() {
+            output.push(c);
+            if c == '\\' {
+                if let Some(c) = self.chars.next() {
+                    output.push(c);
+                }
+            } else if c == quote {
+                break;
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        let mut previous = ' ';
+        for c in self.chars.by_ref() {
+            if previous == '*' && c == '/' {
+                break;
+            }
+            previous = c;
+        }
+    }
+
+    /// Reads the content of a block up to its closing brace verbatim.
+    fn raw_block(&mut self) -> Result<String, String> {
+        let mut output = String::new();
+        let mut depth = 0;
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' | '\'' => self.string(c, &mut output),
+                '/' if self.chars.peek() == Some(&'*') => self.skip_comment(),
+                '{' => {
+                    depth += 1;
+                    output.push(c);
+                }
+                '}' if depth == 0 => return Ok(output),
+                '}' => {
+                    depth -= 1;
+                    output.push(c);
+                }
+                c => output.push(c),
+            }
+        }
+        Err("unclosed block".into())
+    }
+
+    fn block(&mut self, top_level: bool) -> Result<Vec<Node>, String> {
+        let mut nodes = vec![];
+        let mut text = String::new();
+        let mut parens = 0;
+
+        loop {
+            let Some(c) = self.chars.next() else {
+                if !top_level {
+                    return Err("unclosed block".into());
+                }
+                if !text.trim().is_empty() {
+                    nodes.push(Node::Statement(text.trim().to_owned()));
+                }
+                return Ok(nodes);
+            };
+
+            match c {
+                '"' | '\'' => self.string(c, &mut text),
+                '/' if self.chars.peek() == Some(&'*') => self.skip_comment(),
+                c if c.is_whitespace() => {
+                    if !text.ends_with(' ') {
+                        text.push(' ');
+                    }
+                }
+                '(' => {
+                    parens += 1;
+                    text.push(c);
+                }
+                ')' => {
+                    parens -= 1;
+                    text.push(c);
+                }
+                ';' if parens == 0 => {
+                    if !text.trim().is_empty() {
+                        nodes.push(Node::Statement(text.trim().to_owned()));
+                    }
+                    text.clear();
+                }
+                '{' if parens == 0 => {
+                    let prelude = text.trim().to_owned();
+                    text.clear();
+
+                    let at_rule = prelude.strip_prefix('@').map(|rule| {
+                        rule.split(|c: char| c.is_whitespace() || c == '(')
+                            .next()
+                            .unwrap()
+                            .to_owned()
+                    });
+
+                    nodes.push(match at_rule {
+                        Some(name) if !GROUPING_AT_RULES.contains(&name.as_str()) => Node::Raw {
+                            prelude,
+                            block: self.raw_block()?,
+                        },
+                        _ => Node::Block {
+                            prelude,
+                            children: self.block(false)?,
+                        },
+                    });
+                }
+                '}' if parens == 0 => {
+                    if top_level {
+                        return Err("unexpected }".into());
+                    }
+                    if !text.trim().is_empty() {
+                        nodes.push(Node::Statement(text.trim().to_owned()));
+                    }
+                    return Ok(nodes);
+                }
+                c => text.push(c),
+            }
+        }
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Node>, String> {
+    Parser {
+        chars: source.chars().peekable(),
+    }
+    .block(true)
+}
+
+/// Splits `@import url("a.css") screen` into the target and the media query.
+fn parse_import(statement: &str) -> Result<(String, String), String> {
+    let rest = statement["@import".len()..].trim();
+
+    let (target, conditions) = if let Some(rest) = rest.strip_prefix("url(") {
+        let end = rest.find(')').ok_or("unclosed url()")?;
+        (&rest[..end], &rest[end + 1..])
+    } else if let Some(quote @ ('"' | '\'')) = rest.chars().next() {
+        let end = rest[1..].find(quote).ok_or("unclosed string")? + 1;
+        (&rest[..=end], &rest[end + 1..])
+    } else {
+        return Err(format!("invalid import {statement:?}"));
+    };
+
+    let target = target.trim().trim_matches(|c| c == '"' || c == '\'');
+    let conditions = conditions.trim();
+
+    if conditions.starts_with("layer") || conditions.starts_with("supports(") {
+        return Err(format!("unsupported import condition in {statement:?}"));
+    }
+
+    Ok((target.to_owned(), conditions.to_owned()))
+}
+
+/// Parses a stylesheet and recursively inlines its imports.
+fn load(path: &Path, stack: &mut Vec<PathBuf>) -> Result<Vec<Node>, String> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("cannot read {path:?}: {e}"))?;
+
+    if stack.contains(&path) {
+        return Err(format!("import cycle at {path:?}"));
+    }
+
+    let source = std::fs::read_to_string(&path).map_err(|e| format!("{path:?}: {e}"))?;
+    let nodes = parse(&source).map_err(|e| format!("{path:?}: {e}"))?;
+
+    stack.push(path.clone());
+
+    let mut output = vec![];
+    for node in nodes {
+        match node {
+            Node::Statement(statement) if statement.starts_with("@import") => {
+                let (target, conditions) =
+                    parse_import(&statement).map_err(|e| format!("{path:?}: {e}"))?;
+
+                if target.contains("://") || target.starts_with("//") {
+                    return Err(format!("{path:?}: external import of {target}"));
+                }
+
+                let imported = load(&path.parent().unwrap().join(target), stack)?;
+
+                if conditions.is_empty() {
+                    output.extend(imported);
+                } else {
+                    output.push(Node::Block {
+                        prelude: format!("@media {conditions}"),
+                        children: imported,
+                    });
+                }
+            }
+            node => output.push(node),
+        }
+    }
+
+    stack.pop();
+
+    Ok(output)
+}
+
+/// Element names, ids and classes that occur in the generated pages.
+#[derive(Default)]
+struct Used {
+    elements: HashSet<String>,
+    ids: HashSet<String>,
+    classes: HashSet<String>,
+}
+
+impl Used {
+    fn scan(&mut self, html: &str) {
+        for token in html::tokenize(html) {
+            if let Token::StartTag(tag) = token {
+                if let Some(id) = tag.attribute("id") {
+                    self.ids.insert(id.to_owned());
+                }
+                if let Some(class) = tag.attribute("class") {
+                    self.classes
+                        .extend(class.split_whitespace().map(ToOwned::to_owned));
+                }
+                self.elements.insert(tag.name.to_lowercase());
+            }
+        }
+    }
+
+    /// Whether the selector may match anything. This only checks whether all
+    /// elements, ids and classes of the selector are used somewhere, so it
+    /// never removes a selector that is used. Attribute selectors and
+    /// arguments of pseudo-classes are ignored, as they may match state that
+    /// only exists at runtime, like the `data-theme` attribute.
+    fn matches(&self, selector: &str) -> bool {
+        fn identifier(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '\\' {
+                    chars.next();
+                    if let Some(c) = chars.next() {
+                        name.push(c);
+                    }
+                } else if c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+                    name.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            name
+        }
+
+        let mut chars = selector.chars().peekable();
+        let mut compound_start = true;
+
+        while let Some(c) = chars.next() {
+            match c {
+                // known classes and ids are consumed and fall through to `_`
+                '.' if !self.classes.contains(&identifier(&mut chars)) => return false,
+                '#' if !self.ids.contains(&identifier(&mut chars)) => return false,
+                ':' => {
+                    identifier(&mut chars);
+                }
+                '(' | '[' => {
+                    let (open, close) = if c == '(' { ('(', ')') } else { ('[', ']') };
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        if c == open {
+                            depth += 1;
+                        } else if c == close {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                    }
+                }
+                c if compound_start && c.is_alphabetic() => {
+                    let mut name = c.to_string();
+                    name.push_str(&identifier(&mut chars));
+                    if !self.elements.contains(&name.to_lowercase()) {
+                        return false;
+                    }
+                }
+                _ => (),
+            }
+            compound_start = matches!(c, ' ' | '>' | '+' | '~' | ',' | '&');
+        }
+
+        true
+    }
+}
+
+/// Splits a selector list at the top-level commas.
+fn split_selectors(prelude: &str) -> Vec<&str> {
+    let mut selectors = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in prelude.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                selectors.push(prelude[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    selectors.push(prelude[start..].trim());
+    selectors
+}
+
+fn prune(nodes: &[Node], used: &Used) -> Vec<Node> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::Statement(statement) => Some(Node::Statement(statement.clone())),
+            Node::Raw { prelude, block } => Some(Node::Raw {
+                prelude: prelude.clone(),
+                block: block.clone(),
+            }),
+            Node::Block { prelude, children } => {
+                let prelude = if prelude.starts_with('@') {
+                    prelude.clone()
+                } else {
+                    let selectors = split_selectors(prelude)
+                        .into_iter()
+                        .filter(|selector| used.matches(selector))
+                        .collect::<Vec<&str>>();
+                    if selectors.is_empty() {
+                        return None;
+                    }
+                    selectors.join(", ")
+                };
+
+                let children = prune(children, used);
+                if children.is_empty() {
+                    return None;
+                }
+
+                Some(Node::Block { prelude, children })
+            }
+        })
+        .collect()
+}
+
+/// Removes all whitespace that is not significant. The text is expected to
+/// have its whitespace collapsed already.
+fn minify_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                output.push(c);
+                while let Some(s) = chars.next() {
+                    output.push(s);
+                    if s == '\\' {
+                        if let Some(s) = chars.next() {
+                            output.push(s);
+                        }
+                    } else if s == c {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                let previous = output.chars().last();
+                let next = chars.peek();
+                if previous.is_none()
+                    || next.is_none()
+                    || previous.is_some_and(|c| "{};,>(:".contains(c))
+                    || next.is_some_and(|c| "{};,>)".contains(*c))
+                {
+                    continue;
+                }
+                output.push(' ');
+            }
+            c => output.push(c),
+        }
+    }
+
+    output
+}
+
+fn serialize(nodes: &[Node], minify: bool, depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+
+    for node in nodes {
+        match node {
+            Node::Statement(statement) => {
+                if minify {
+                    output.push_str(&minify_text(statement));
+                    output.push(';');
+                } else {
+                    output.push_str(&format!("{indent}{statement};\n"));
+                }
+            }
+            Node::Block { prelude, children } => {
+                if minify {
+                    output.push_str(&minify_text(prelude));
+                    output.push('{');
+                    serialize(children, minify, depth + 1, output);
+                    if output.ends_with(';') {
+                        output.pop();
+                    }
+                    output.push('}');
+                } else {
+                    output.push_str(&format!("{indent}{prelude} {{\n"));
+                    serialize(children, minify, depth + 1, output);
+                    output.push_str(&format!("{indent}}}\n"));
+                }
+            }
+            Node::Raw { prelude, block } => {
+                if minify {
+                    output.push_str(&format!(
+                        "{}{{{}}}",
+                        minify_text(prelude),
+                        minify_text(block.trim())
+                    ));
+                } else {
+                    output.push_str(&format!("{indent}{prelude} {{{block}}}\n"));
+                }
+            }
+        }
+    }
+}
+
+fn to_string(nodes: &[Node], minify: bool) -> String {
+    let mut output = String::new();
+    serialize(nodes, minify, 0, &mut output);
+    output
+}
+
+/// Prefixes the selectors of all top-level style rules with `scope`. The
+/// selector `root` is replaced by `scope` itself.
+pub fn scope(source: &str, scope: &str, root: &str) -> Result<String, String> {
+    let nodes = parse(source)?
+        .into_iter()
+        .map(|node| match node {
+            Node::Block { prelude, children } if !prelude.starts_with('@') => Node::Block {
+                prelude: split_selectors(&prelude)
+                    .into_iter()
+                    .map(|selector| {
+                        if selector == root {
+                            scope.to_owned()
+                        } else {
+                            format!("{scope} {selector}")
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                children,
+            },
+            node => node,
+        })
+        .collect::<Vec<Node>>();
+
+    Ok(to_string(&nodes, false))
+}
+
+/// Bundles the generated stylesheets and all configured stylesheets into a
+/// single fingerprinted file and points all pages to it.
+pub fn bundle(
+    config: &CssConfig,
+    generated: &[String],
+    input_path: &Path,
+    output_base_path: &Path,
+    pages: &[Page],
+) -> Page {
+    let mut nodes = vec![];
+    for stylesheet in generated {
+        nodes.extend(parse(stylesheet).unwrap_or_else(|e| panic!("generated stylesheet: {e}")));
+    }
+    for stylesheet in &config.stylesheets {
+        nodes.extend(
+            load(&input_path.join("static").join(stylesheet), &mut vec![])
+                .unwrap_or_else(|e| panic!("{e}")),
+        );
+    }
+
+    let html_pages = pages
+        .iter()
+        .filter(|page| matches!(page.filetype, FileType::Html))
+        .map(|page| {
+            let path = output_base_path.join(&page.path);
+            let content = std::fs::read_to_string(&path).unwrap();
+            (path, content)
+        })
+        .collect::<Vec<(PathBuf, String)>>();
+
+    if config.remove_unused {
+        let mut used = Used::default();
+        for (_path, content) in &html_pages {
+            used.scan(content);
+        }
+        nodes = prune(&nodes, &used);
+    }
+
+    let stylesheet = to_string(&nodes, config.minify);
+
+    let hash = Sha256::digest(stylesheet.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    let path = format!("style.{}.css", &hash[..16]);
+
+    std::fs::write(output_base_path.join(&path), &stylesheet).unwrap();
+
+    for (page_path, content) in html_pages {
+        let replacement = if config.inline_critical {
+            // the rules the page needs, the full stylesheet is loaded without
+            // blocking rendering, for pages that are loaded via htmx
+            let mut used = Used::default();
+            used.scan(&content);
+            let critical = to_string(&prune(&nodes, &used), config.minify);
+
+            format!(
+                "<style>{critical}</style>\
+                 <link rel=\"preload\" as=\"style\" href=\"/{path}\" onload=\"this.onload=null;this.rel='stylesheet'\">\
+                 <noscript><link rel=\"stylesheet\" href=\"/{path}\"></noscript>"
+            )
+        } else {
+            format!("<link rel=\"stylesheet\" href=\"/{path}\">")
+        };
+
+        // the page may be minified, so the exact markup of the link is unknown
+        let (start, end) = content
+            .find(PLACEHOLDER)
+            .and_then(|position| {
+                let start = content[..position].rfind("<link")?;
+                let end = position + content[position..].find('>')? + 1;
+                Some((start, end))
+            })
+            .unwrap_or_else(|| panic!("{page_path:?} does not reference the stylesheet"));
+
+        std::fs::write(
+            &page_path,
+            format!("{}{replacement}{}", &content[..start], &content[end..]),
+        )
+        .unwrap();
+    }
+
+    Page {
+        path,
+        filetype: FileType::Css,
+    }
+}
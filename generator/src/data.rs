@@ -1,11 +1,8 @@
 use std::fmt;
-use std::path::Path;
 
 use crate::icon;
 use crate::icon::Icon;
 
-pub const FULLNAME: &str = "Hannes Körber";
-
 pub struct Certification {
     pub link: &'static str,
     pub title: &'static str,
@@ -20,48 +17,6 @@ pub fn certifications() -> Vec<Certification> {
     }]
 }
 
-pub struct Social {
-    pub name: &'static str,
-    pub link: String,
-    pub icon: Box<dyn icon::Icon>,
-    pub description: Option<&'static str>,
-}
-
-pub fn socials(input_path: &Path) -> Vec<Social> {
-    vec![
-        Social {
-            name: "Github",
-            link: "https://github.com/hakoerber".into(),
-            icon: icon!("Github", input_path),
-            description: None,
-        },
-        Social {
-            name: "Linkedin",
-            link: "https://www.linkedin.com/in/hannes-koerber".into(),
-            icon: icon!("Linkedin", input_path),
-            description: None,
-        },
-        Social {
-            name: "Keybase",
-            link: "https://keybase.io/hakoerber".into(),
-            icon: icon!("Keybase", input_path),
-            description: None,
-        },
-        Social {
-            name: "E-Mail",
-            link: "mailto:hannes.koerber@gmail.com".into(),
-            icon: icon!("Email", input_path),
-            description: Some("Send me an e-mail"),
-        },
-        Social {
-            name: "RSS",
-            link: "/rss.xml".into(),
-            icon: icon!("Rss", input_path),
-            description: Some("Follow my blog on RSS"),
-        },
-    ]
-}
-
 pub enum TechLevel {
     Pro,
     Normal,
@@ -91,7 +46,7 @@ pub struct TechCategory {
     pub technologies: Vec<Technology>,
 }
 
-pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
+pub fn tech_categories(icons: &icon::IconRegistry) -> Vec<TechCategory> {
     vec![
         TechCategory {
             name: "Containerization",
@@ -99,32 +54,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Kubernetes",
                     level: TechLevel::Pro,
-                    icon: icon!("Kubernetes", input_path),
+                    icon: icon!("Kubernetes", icons),
                 },
                 Technology {
                     name: "Docker",
                     level: TechLevel::Pro,
-                    icon: icon!("Docker", input_path),
+                    icon: icon!("Docker", icons),
                 },
                 Technology {
                     name: "cri-o",
                     level: TechLevel::Normal,
-                    icon: icon!("CriO", input_path),
+                    icon: icon!("CriO", icons),
                 },
                 Technology {
                     name: "Containerd",
                     level: TechLevel::Normal,
-                    icon: icon!("Containerd", input_path),
+                    icon: icon!("Containerd", icons),
                 },
                 Technology {
                     name: "OCI",
                     level: TechLevel::Normal,
-                    icon: icon!("Oci", input_path),
+                    icon: icon!("Oci", icons),
                 },
                 Technology {
                     name: "Rancher",
                     level: TechLevel::Normal,
-                    icon: icon!("Rancher", input_path),
+                    icon: icon!("Rancher", icons),
                 },
             ],
         },
@@ -134,32 +89,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "PostgreSQL",
                     level: TechLevel::Pro,
-                    icon: icon!("Postgresql", input_path),
+                    icon: icon!("Postgresql", icons),
                 },
                 Technology {
                     name: "ElasticSearch",
                     level: TechLevel::Pro,
-                    icon: icon!("Elasticsearch", input_path),
+                    icon: icon!("Elasticsearch", icons),
                 },
                 Technology {
                     name: "MySQL",
                     level: TechLevel::Pro,
-                    icon: icon!("Mysql", input_path),
+                    icon: icon!("Mysql", icons),
                 },
                 Technology {
                     name: "Redis",
                     level: TechLevel::Normal,
-                    icon: icon!("Redis", input_path),
+                    icon: icon!("Redis", icons),
                 },
                 Technology {
                     name: "InfluxDB",
                     level: TechLevel::Normal,
-                    icon: icon!("Influx", input_path),
+                    icon: icon!("Influx", icons),
                 },
                 Technology {
                     name: "SQLite",
                     level: TechLevel::Normal,
-                    icon: icon!("Sqlite", input_path),
+                    icon: icon!("Sqlite", icons),
                 },
             ],
         },
@@ -169,32 +124,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Terraform",
                     level: TechLevel::Pro,
-                    icon: icon!("Terraform", input_path),
+                    icon: icon!("Terraform", icons),
                 },
                 Technology {
                     name: "Ansible",
                     level: TechLevel::Pro,
-                    icon: icon!("Ansible", input_path),
+                    icon: icon!("Ansible", icons),
                 },
                 Technology {
                     name: "Pulumi",
                     level: TechLevel::Pro,
-                    icon: icon!("Pulumi", input_path),
+                    icon: icon!("Pulumi", icons),
                 },
                 Technology {
                     name: "Packer",
                     level: TechLevel::Normal,
-                    icon: icon!("Packer", input_path),
+                    icon: icon!("Packer", icons),
                 },
                 Technology {
                     name: "Puppet",
                     level: TechLevel::Normal,
-                    icon: icon!("Puppet", input_path),
+                    icon: icon!("Puppet", icons),
                 },
                 Technology {
                     name: "SaltStack",
                     level: TechLevel::Normal,
-                    icon: icon!("Saltstack", input_path),
+                    icon: icon!("Saltstack", icons),
                 },
             ],
         },
@@ -204,32 +159,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "HTML",
                     level: TechLevel::Pro,
-                    icon: icon!("Html5", input_path),
+                    icon: icon!("Html5", icons),
                 },
                 Technology {
                     name: "CCS",
                     level: TechLevel::Pro,
-                    icon: icon!("Css", input_path),
+                    icon: icon!("Css", icons),
                 },
                 Technology {
                     name: "JavaScript",
                     level: TechLevel::Normal,
-                    icon: icon!("Javascript", input_path),
+                    icon: icon!("Javascript", icons),
                 },
                 Technology {
                     name: "Flask",
                     level: TechLevel::Normal,
-                    icon: icon!("Flask", input_path),
+                    icon: icon!("Flask", icons),
                 },
                 Technology {
                     name: "Svelte",
                     level: TechLevel::Normal,
-                    icon: icon!("Svelte", input_path),
+                    icon: icon!("Svelte", icons),
                 },
                 Technology {
                     name: "ReactJS",
                     level: TechLevel::Normal,
-                    icon: icon!("Reactjs", input_path),
+                    icon: icon!("Reactjs", icons),
                 },
             ],
         },
@@ -239,32 +194,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Python",
                     level: TechLevel::Pro,
-                    icon: icon!("Python", input_path),
+                    icon: icon!("Python", icons),
                 },
                 Technology {
                     name: "Rust",
                     level: TechLevel::Pro,
-                    icon: icon!("Rust", input_path),
+                    icon: icon!("Rust", icons),
                 },
                 Technology {
                     name: "Go",
                     level: TechLevel::Pro,
-                    icon: icon!("Go", input_path),
+                    icon: icon!("Go", icons),
                 },
                 Technology {
                     name: "TypeScript",
                     level: TechLevel::Normal,
-                    icon: icon!("Typescript", input_path),
+                    icon: icon!("Typescript", icons),
                 },
                 Technology {
                     name: "Bash",
                     level: TechLevel::Normal,
-                    icon: icon!("Bash", input_path),
+                    icon: icon!("Bash", icons),
                 },
                 Technology {
                     name: "C",
                     level: TechLevel::Normal,
-                    icon: icon!("C", input_path),
+                    icon: icon!("C", icons),
                 },
             ],
         },
@@ -274,32 +229,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Prometheus",
                     level: TechLevel::Pro,
-                    icon: icon!("Prometheus", input_path),
+                    icon: icon!("Prometheus", icons),
                 },
                 Technology {
                     name: "Grafana",
                     level: TechLevel::Pro,
-                    icon: icon!("Grafana", input_path),
+                    icon: icon!("Grafana", icons),
                 },
                 Technology {
                     name: "Kibana",
                     level: TechLevel::Normal,
-                    icon: icon!("Kibana", input_path),
+                    icon: icon!("Kibana", icons),
                 },
                 Technology {
                     name: "OpsGenie",
                     level: TechLevel::Normal,
-                    icon: icon!("Opsgenie", input_path),
+                    icon: icon!("Opsgenie", icons),
                 },
                 Technology {
                     name: "OpenTelemetry",
                     level: TechLevel::Normal,
-                    icon: icon!("Opentelemetry", input_path),
+                    icon: icon!("Opentelemetry", icons),
                 },
                 Technology {
                     name: "Jaeger",
                     level: TechLevel::Normal,
-                    icon: icon!("Jaeger", input_path),
+                    icon: icon!("Jaeger", icons),
                 },
             ],
         },
@@ -309,32 +264,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Git",
                     level: TechLevel::Pro,
-                    icon: icon!("Git", input_path),
+                    icon: icon!("Git", icons),
                 },
                 Technology {
                     name: "Neovim",
                     level: TechLevel::Pro,
-                    icon: icon!("Neovim", input_path),
+                    icon: icon!("Neovim", icons),
                 },
                 Technology {
                     name: "GitLab",
                     level: TechLevel::Normal,
-                    icon: icon!("Gitlab", input_path),
+                    icon: icon!("Gitlab", icons),
                 },
                 Technology {
                     name: "GitHub",
                     level: TechLevel::Normal,
-                    icon: icon!("Github", input_path),
+                    icon: icon!("Github", icons),
                 },
                 Technology {
                     name: "OpenAPI",
                     level: TechLevel::Normal,
-                    icon: icon!("Swagger", input_path),
+                    icon: icon!("Swagger", icons),
                 },
                 Technology {
                     name: "Jira",
                     level: TechLevel::Normal,
-                    icon: icon!("Jira", input_path),
+                    icon: icon!("Jira", icons),
                 },
             ],
         },
@@ -344,17 +299,17 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Drone",
                     level: TechLevel::Pro,
-                    icon: icon!("Drone", input_path),
+                    icon: icon!("Drone", icons),
                 },
                 Technology {
                     name: "GitLab CI",
                     level: TechLevel::Pro,
-                    icon: icon!("Gitlab", input_path),
+                    icon: icon!("Gitlab", icons),
                 },
                 Technology {
                     name: "Jenkins",
                     level: TechLevel::Normal,
-                    icon: icon!("Jenkins", input_path),
+                    icon: icon!("Jenkins", icons),
                 },
             ],
         },
@@ -364,17 +319,17 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "AWS",
                     level: TechLevel::Pro,
-                    icon: icon!("Aws", input_path),
+                    icon: icon!("Aws", icons),
                 },
                 Technology {
                     name: "DigitalOcean",
                     level: TechLevel::Normal,
-                    icon: icon!("Digitalocean", input_path),
+                    icon: icon!("Digitalocean", icons),
                 },
                 Technology {
                     name: "Hetzner",
                     level: TechLevel::Normal,
-                    icon: icon!("Hetzner", input_path),
+                    icon: icon!("Hetzner", icons),
                 },
             ],
         },
@@ -384,22 +339,22 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Nginx",
                     level: TechLevel::Pro,
-                    icon: icon!("Nginx", input_path),
+                    icon: icon!("Nginx", icons),
                 },
                 Technology {
                     name: "Apache",
                     level: TechLevel::Normal,
-                    icon: icon!("Apache", input_path),
+                    icon: icon!("Apache", icons),
                 },
                 Technology {
                     name: "HAProxy",
                     level: TechLevel::Normal,
-                    icon: icon!("Haproxy", input_path),
+                    icon: icon!("Haproxy", icons),
                 },
                 Technology {
                     name: "OpenResty",
                     level: TechLevel::Normal,
-                    icon: icon!("Openresty", input_path),
+                    icon: icon!("Openresty", icons),
                 },
             ],
         },
@@ -409,32 +364,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "CentOS",
                     level: TechLevel::Pro,
-                    icon: icon!("Centos", input_path),
+                    icon: icon!("Centos", icons),
                 },
                 Technology {
                     name: "Debian",
                     level: TechLevel::Pro,
-                    icon: icon!("Debian", input_path),
+                    icon: icon!("Debian", icons),
                 },
                 Technology {
                     name: "Arch Linux",
                     level: TechLevel::Pro,
-                    icon: icon!("ArchLinux", input_path),
+                    icon: icon!("ArchLinux", icons),
                 },
                 Technology {
                     name: "Ubuntu",
                     level: TechLevel::Normal,
-                    icon: icon!("Ubuntu", input_path),
+                    icon: icon!("Ubuntu", icons),
                 },
                 Technology {
                     name: "Fedora",
                     level: TechLevel::Normal,
-                    icon: icon!("Fedora", input_path),
+                    icon: icon!("Fedora", icons),
                 },
                 Technology {
                     name: "FreeBSD",
                     level: TechLevel::Normal,
-                    icon: icon!("Freebsd", input_path),
+                    icon: icon!("Freebsd", icons),
                 },
             ],
         },
@@ -444,32 +399,32 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Keycloak",
                     level: TechLevel::Pro,
-                    icon: icon!("Keycloak", input_path),
+                    icon: icon!("Keycloak", icons),
                 },
                 Technology {
                     name: "OpenID Connect",
                     level: TechLevel::Pro,
-                    icon: icon!("OpenidConnect", input_path),
+                    icon: icon!("OpenidConnect", icons),
                 },
                 Technology {
                     name: "GnuPG",
                     level: TechLevel::Normal,
-                    icon: icon!("Gnupg", input_path),
+                    icon: icon!("Gnupg", icons),
                 },
                 Technology {
                     name: "Let's Encrypt",
                     level: TechLevel::Normal,
-                    icon: icon!("Letsencrypt", input_path),
+                    icon: icon!("Letsencrypt", icons),
                 },
                 Technology {
                     name: "Wireshark",
                     level: TechLevel::Normal,
-                    icon: icon!("Wireshark", input_path),
+                    icon: icon!("Wireshark", icons),
                 },
                 Technology {
                     name: "OpenVPN",
                     level: TechLevel::Normal,
-                    icon: icon!("Openvpn", input_path),
+                    icon: icon!("Openvpn", icons),
                 },
             ],
         },
@@ -479,17 +434,17 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Libvirt / KVM",
                     level: TechLevel::Pro,
-                    icon: icon!("Libvirt", input_path),
+                    icon: icon!("Libvirt", icons),
                 },
                 Technology {
                     name: "Vagrant",
                     level: TechLevel::Pro,
-                    icon: icon!("Vagrant", input_path),
+                    icon: icon!("Vagrant", icons),
                 },
                 Technology {
                     name: "Qemu",
                     level: TechLevel::Normal,
-                    icon: icon!("Qemu", input_path),
+                    icon: icon!("Qemu", icons),
                 },
             ],
         },
@@ -499,12 +454,12 @@ pub fn tech_categories(input_path: &Path) -> Vec<TechCategory> {
                 Technology {
                     name: "Ceph",
                     level: TechLevel::Pro,
-                    icon: icon!("Ceph", input_path),
+                    icon: icon!("Ceph", icons),
                 },
                 Technology {
                     name: "ZFS",
                     level: TechLevel::Normal,
-                    icon: icon!("Openzfs", input_path),
+                    icon: icon!("Openzfs", icons),
                 },
             ],
         },
@@ -542,11 +497,11 @@ pub struct ContributionProject {
     pub links: ProjectLinks,
 }
 
-pub fn projects(input_path: &Path) -> Vec<Project> {
+pub fn projects(icons: &icon::IconRegistry) -> Vec<Project> {
     vec![
         Project {
             title: "git-repo-manager",
-            figure: Some(ProjectFigure::Icon(icon!("Git", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Git", icons))),
             description: vec!["A command-line tool to manage local git repositories"],
             tags: ProjectTags {
                 languages: vec!["Rust"],
@@ -559,7 +514,7 @@ pub fn projects(input_path: &Path) -> Vec<Project> {
         },
         Project {
             title: "prometheus-restic-backblaze",
-            figure: Some(ProjectFigure::Icon(icon!("Backblaze", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Backblaze", icons))),
             description: vec!["A prometheus exporter that reports restic backup ages for Backblaze"],
             tags: ProjectTags {
                 languages: vec!["Python"],
@@ -585,7 +540,7 @@ pub fn projects(input_path: &Path) -> Vec<Project> {
         },
         Project {
             title: "aws-glacier-backup",
-            figure: Some(ProjectFigure::Icon(icon!("AwsS3", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("AwsS3", icons))),
             description: vec!["A bash script that uploads gzip’ed, gpg encrypted backups to AWS glacier"],
             tags: ProjectTags {
                 languages: vec!["Bash"],
@@ -644,7 +599,7 @@ pub fn projects(input_path: &Path) -> Vec<Project> {
         },
         Project {
             title: "salt-nginx-letsencrypt",
-            figure: Some(ProjectFigure::Icon(icon!("Letsencrypt", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Letsencrypt", icons))),
             description: vec!["A SaltStack nginx formula that also enables automated letsencrypt certificate management"],
             tags: ProjectTags {
                 languages: vec!["Python"],
@@ -657,7 +612,7 @@ pub fn projects(input_path: &Path) -> Vec<Project> {
         },
         Project {
             title: "ansible-roles",
-            figure: Some(ProjectFigure::Icon(icon!("Ansible", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Ansible", icons))),
             description: vec!["A collection of ansible roles, e.g. for libvirt, networking, OpenVPN"],
             tags: ProjectTags {
                 languages: vec!["YAML"],
@@ -670,7 +625,7 @@ pub fn projects(input_path: &Path) -> Vec<Project> {
         },
         Project {
             title: "salt-states",
-            figure: Some(ProjectFigure::Icon(icon!("Saltstack", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Saltstack", icons))),
             description: vec![
                 concat!(
                     "A big collection of saltstack states that I used for my ",
@@ -721,11 +676,11 @@ pub fn projects(input_path: &Path) -> Vec<Project> {
     ]
 }
 
-pub fn contribution_projects(input_path: &Path) -> Vec<ContributionProject> {
+pub fn contribution_projects(icons: &icon::IconRegistry) -> Vec<ContributionProject> {
     vec![
         ContributionProject {
             title: "Prometheus Node Exporter",
-            figure: Some(ProjectFigure::Icon(icon!("Prometheus", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Prometheus", icons))),
             contributions: vec![
                 "Add label to NFS metrics containing the NFS protocol (<code>tcp/udp</code>)",
             ],
@@ -741,7 +696,7 @@ pub fn contribution_projects(input_path: &Path) -> Vec<ContributionProject> {
         },
         ContributionProject {
             title: "Kubespray",
-            figure: Some(ProjectFigure::Icon(icon!("Kubernetes", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Kubernetes", icons))),
             contributions: vec![
                 "Fix issues with continuous regeneration of etcd TLS cerificates",
                 "Fix incorrect directory mode for etcd TLS certificates",
@@ -758,7 +713,7 @@ pub fn contribution_projects(input_path: &Path) -> Vec<ContributionProject> {
         },
         ContributionProject {
             title: "SaltStack",
-            figure: Some(ProjectFigure::Icon(icon!("Saltstack", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Saltstack", icons))),
             contributions: vec![
                 "Expand the <code>firewalld</code> module for interfaces, sources, services and zones",
                 "Fix the reactor engine not being loaded when not explicitly configured",
@@ -774,7 +729,7 @@ pub fn contribution_projects(input_path: &Path) -> Vec<ContributionProject> {
         },
         ContributionProject {
             title: "Vagrant",
-            figure: Some(ProjectFigure::Icon(icon!("Vagrant", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Vagrant", icons))),
             contributions: vec![
                 "Renew DHCP lease on hostname change for Debian guests",
                 "Fix hostname entry in <code>/etc/hosts</code> for Debian guests",
@@ -790,7 +745,7 @@ pub fn contribution_projects(input_path: &Path) -> Vec<ContributionProject> {
         },
         ContributionProject {
             title: "Prometheus procfs",
-            figure: Some(ProjectFigure::Icon(icon!("Prometheus", input_path))),
+            figure: Some(ProjectFigure::Icon(icon!("Prometheus", icons))),
             contributions: vec![
                 "Add exporting of a new field containing the NFS protocol (required for the node exporter change)",
                 "Fix parsing of the <code>xprt</code> lines in <code>mountstats</code> to enable metric exports for UDP mounts",
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use time::format_description::well_known::Rfc3339;
+
+use super::blog;
+use super::config::SiteConfig;
+use super::{Blogpost, FileType, Frontmatter, Page};
+
+/// Where the feed of all posts is published, relative to the root of the site
+pub const ATOM_PATH: &str = "atom.xml";
+
+pub const SITEMAP_PATH: &str = "sitemap.xml";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn timestamp(timestamp: time::OffsetDateTime) -> String {
+    timestamp.format(&Rfc3339).unwrap()
+}
+
+fn write(output_base_path: &Path, path: &str, content: &str) -> Page {
+    std::fs::write(output_base_path.join(path), content).unwrap();
+
+    Page {
+        path: path.to_owned(),
+        filetype: FileType::Xml,
+    }
+}
+
+/// An Atom feed of all posts. `blog_posts` are sorted newest first.
+pub fn atom(output_base_path: &Path, blog_posts: &[Blogpost], config: &SiteConfig) -> Page {
+    let updated = blog_posts
+        .iter()
+        .map(|blog_post| blog_post.frontmatter.last_modified())
+        .max()
+        .unwrap_or(time::OffsetDateTime::UNIX_EPOCH);
+
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    feed.push_str(&format!(
+        "<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"{}\">",
+        escape(&config.language)
+    ));
+    feed.push_str(&format!("<title>{}</title>", escape(&config.title)));
+    feed.push_str(&format!(
+        "<subtitle>{}</subtitle>",
+        escape(&config.description)
+    ));
+    feed.push_str(&format!(
+        "<link href=\"{}\" rel=\"self\" type=\"application/atom+xml\"/>",
+        escape(&config.url(ATOM_PATH))
+    ));
+    feed.push_str(&format!(
+        "<link href=\"{}\" rel=\"alternate\" type=\"text/html\"/>",
+        escape(&config.url(&config.blog.link("index.html")))
+    ));
+    feed.push_str(&format!("<id>{}</id>", escape(&config.url("/"))));
+    feed.push_str(&format!("<updated>{}</updated>", timestamp(updated)));
+    feed.push_str(&format!(
+        "<author><name>{}</name><uri>{}</uri></author>",
+        escape(&config.author),
+        escape(&config.url("/"))
+    ));
+
+    for blog_post in blog_posts {
+        let frontmatter = &blog_post.frontmatter;
+        let url = escape(&config.url(&config.blog.link(&blog_post.html_filename)));
+
+        feed.push_str("<entry>");
+        feed.push_str(&format!("<title>{}</title>", escape(&frontmatter.title)));
+        feed.push_str(&format!(
+            "<link href=\"{url}\" rel=\"alternate\" type=\"text/html\"/>"
+        ));
+        feed.push_str(&format!("<id>{url}</id>"));
+        feed.push_str(&format!(
+            "<published>{}</published>",
+            timestamp(frontmatter.timestamp)
+        ));
+        feed.push_str(&format!(
+            "<updated>{}</updated>",
+            timestamp(frontmatter.last_modified())
+        ));
+        // the summary may contain markup
+        feed.push_str(&format!(
+            "<summary type=\"html\">{}</summary>",
+            escape(&format!(
+                "<p>{}</p><p>{} words, {} min read</p>",
+                frontmatter.summary,
+                blog_post.words,
+                blog::reading_time(blog_post.words)
+            ))
+        ));
+        for tag in &frontmatter.tags {
+            feed.push_str(&format!("<category term=\"{}\"/>", escape(&tag.0)));
+        }
+        feed.push_str("</entry>");
+    }
+
+    feed.push_str("</feed>\n");
+
+    write(output_base_path, ATOM_PATH, &feed)
+}
+
+/// A sitemap of all HTML pages. Pages get the date they were last modified
+/// according to their git history in `modified`, keyed by path. For posts an
+/// `updated` date in their frontmatter takes precedence.
+pub fn sitemap(
+    output_base_path: &Path,
+    pages: &[Page],
+    blog_posts: &[Blogpost],
+    modified: &BTreeMap<String, time::OffsetDateTime>,
+    config: &SiteConfig,
+) -> Page {
+    let posts: BTreeMap<String, &Frontmatter> = blog_posts
+        .iter()
+        .map(|blog_post| {
+            (
+                format!("{}/{}", config.blog.path, blog_post.html_filename),
+                &blog_post.frontmatter,
+            )
+        })
+        .collect();
+
+    let mut sitemap = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    sitemap.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">");
+
+    for page in pages
+        .iter()
+        .filter(|page| matches!(page.filetype, FileType::Html))
+    {
+        sitemap.push_str("<url>");
+        sitemap.push_str(&format!("<loc>{}</loc>", escape(&config.url(&page.path))));
+        let history = modified.get(&page.path).copied();
+        let lastmod = match posts.get(&page.path) {
+            Some(frontmatter) => Some(
+                frontmatter
+                    .updated
+                    .or(history)
+                    .unwrap_or(frontmatter.timestamp),
+            ),
+            None => history,
+        };
+        if let Some(lastmod) = lastmod {
+            sitemap.push_str(&format!("<lastmod>{}</lastmod>", timestamp(lastmod)));
+        }
+        sitemap.push_str("</url>");
+    }
+
+    sitemap.push_str("</urlset>\n");
+
+    write(output_base_path, SITEMAP_PATH, &sitemap)
+}
@@ -2,10 +2,17 @@ use std::fs;
 use std::io;
 use std::path::Path;
 
-pub fn copy_dir_all(
+/// Processes the content of a file, given its path. `None` leaves the file out.
+type Transform<'a> = dyn Fn(&Path, Vec<u8>) -> io::Result<Option<Vec<u8>>> + 'a;
+
+/// Copies all files below `src` to `dst`, relative to `base`, and passes the
+/// content of every file through `process` before writing it. Returns the
+/// copied paths relative to `base`.
+pub fn copy_dir_all_with(
     src: impl AsRef<Path>,
     base: impl AsRef<Path>,
     dst: impl AsRef<Path>,
+    process: &Transform<'_>,
 ) -> io::Result<Vec<String>> {
     fs::create_dir_all(base.as_ref().join(&dst))?;
 
@@ -14,15 +21,19 @@ pub fn copy_dir_all(
         let entry = entry?;
         let filetype = entry.file_type()?;
         if filetype.is_dir() {
-            paths.append(&mut copy_dir_all(
+            paths.append(&mut copy_dir_all_with(
                 entry.path(),
                 base.as_ref(),
                 dst.as_ref().join(entry.file_name()),
+                process,
             )?);
         } else {
-            fs::copy(
-                entry.path(),
+            let Some(content) = process(&entry.path(), fs::read(entry.path())?)? else {
+                continue;
+            };
+            fs::write(
                 base.as_ref().join(dst.as_ref().join(entry.file_name())),
+                content,
             )?;
             paths.push(
                 dst.as_ref()
@@ -35,3 +46,22 @@ pub fn copy_dir_all(
     }
     Ok(paths)
 }
+
+/// Paths of all files below `directory`, relative to it.
+pub fn list_files(directory: impl AsRef<Path>) -> io::Result<Vec<String>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name().into_string().unwrap();
+        if entry.file_type()?.is_dir() {
+            paths.extend(
+                list_files(entry.path())?
+                    .into_iter()
+                    .map(|path| format!("{name}/{path}")),
+            );
+        } else {
+            paths.push(name);
+        }
+    }
+    Ok(paths)
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use maud::{html, Markup};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::config::SiteConfig;
+
+pub struct Commit {
+    pub timestamp: OffsetDateTime,
+    pub author: String,
+    pub subject: String,
+}
+
+/// What a page is made from, relative to the content directory
+#[derive(Clone, Copy)]
+pub enum Source<'a> {
+    File(&'a str),
+    /// The lines of `file` in `range`, in the syntax of `git log -L`, e.g. a
+    /// single function. `^/regex/` searches from the start of the file, not
+    /// from the end of the previous range.
+    Lines {
+        file: &'a str,
+        range: &'a str,
+    },
+}
+
+impl Source<'_> {
+    fn file(&self) -> &str {
+        match self {
+            Self::File(file) | Self::Lines { file, .. } => file,
+        }
+    }
+}
+
+/// The commits of all files of the content directory, read once from the local
+/// repository. Empty if there is no repository.
+#[derive(Default)]
+pub struct History {
+    /// The content directory
+    directory: PathBuf,
+    /// The path of the content directory in the repository, empty or ending
+    /// with a slash
+    prefix: String,
+    /// Newest first
+    commits: Vec<Commit>,
+    /// Maps the hash of every commit to its index
+    hashes: BTreeMap<String, usize>,
+    /// Maps every file, relative to the content directory, to the indices of
+    /// its commits
+    files: BTreeMap<String, Vec<usize>>,
+}
+
+fn git(directory: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {e}"))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| format!("invalid output of git: {e}"))
+}
+
+impl History {
+    pub fn load(input_path: &Path) -> Self {
+        Self::read(input_path).unwrap_or_else(|e| {
+            println!("warning: pages are rendered without their git history: {e}");
+            Self::default()
+        })
+    }
+
+    fn read(input_path: &Path) -> Result<Self, String> {
+        let prefix = git(input_path, &["rev-parse", "--show-prefix"])?
+            .trim()
+            .to_owned();
+
+        // each commit starts with a record separator, followed by the files it
+        // changed on separate lines. Renames show up as a new file.
+        let log = git(
+            input_path,
+            &[
+                "-c",
+                "core.quotePath=off",
+                "log",
+                "--relative",
+                "--no-renames",
+                "--name-only",
+                "--format=%x1e%H%x1f%aI%x1f%an%x1f%s",
+                "--",
+                ".",
+            ],
+        )?;
+
+        let mut history = Self {
+            directory: input_path.to_owned(),
+            prefix,
+            ..Self::default()
+        };
+
+        for record in log.split('\x1e').skip(1) {
+            let mut lines = record.lines();
+            let header = lines.next().unwrap_or_default();
+            let fields: Vec<&str> = header.splitn(4, '\x1f').collect();
+            let [hash, timestamp, author, subject] = fields[..] else {
+                return Err(format!("unexpected output of git log: {header:?}"));
+            };
+
+            let id = history.commits.len();
+            history.hashes.insert(hash.to_owned(), id);
+            history.commits.push(Commit {
+                timestamp: OffsetDateTime::parse(timestamp, &Rfc3339)
+                    .map_err(|e| format!("invalid commit date {timestamp:?}: {e}"))?,
+                author: author.to_owned(),
+                subject: subject.to_owned(),
+            });
+
+            for path in lines.filter(|line| !line.is_empty()) {
+                history.files.entry(path.to_owned()).or_default().push(id);
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// The commits that changed any of the line ranges of `sources`
+    fn lines(&self, sources: &[Source]) -> Vec<usize> {
+        let mut args = vec![
+            "log".to_owned(),
+            "--no-patch".to_owned(),
+            "--format=%H".to_owned(),
+        ];
+        for source in sources {
+            if let Source::Lines { file, range } = source {
+                args.push(format!("-L{range}:{file}"));
+            }
+        }
+        if args.len() == 3 {
+            return vec![];
+        }
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match git(&self.directory, &args) {
+            Ok(log) => log
+                .lines()
+                .filter_map(|hash| self.hashes.get(hash))
+                .copied()
+                .collect(),
+            Err(e) => {
+                println!(
+                    "warning: history of {} cannot be read: {e}",
+                    sources[0].file()
+                );
+                vec![]
+            }
+        }
+    }
+
+    /// The history of a page made from `sources`. The history of the file of
+    /// the first source is linked.
+    pub fn page(&self, sources: &[Source]) -> Option<PageHistory<'_>> {
+        if self.commits.is_empty() {
+            return None;
+        }
+
+        let mut ids: Vec<usize> = sources
+            .iter()
+            .filter_map(|source| match source {
+                Source::File(file) => self.files.get(*file),
+                Source::Lines { .. } => None,
+            })
+            .flatten()
+            .copied()
+            .chain(self.lines(sources))
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let commits: Vec<&Commit> = ids.into_iter().map(|id| &self.commits[id]).collect();
+        if commits.is_empty() {
+            return None;
+        }
+
+        Some(PageHistory {
+            source: format!("{}{}", self.prefix, sources[0].file()),
+            commits,
+        })
+    }
+
+    /// When a page made from `sources` was last changed, if it has a history.
+    pub fn last_modified(&self, sources: &[Source]) -> Option<OffsetDateTime> {
+        self.page(sources).map(|history| history.last_modified())
+    }
+}
+
+pub struct PageHistory<'a> {
+    /// Relative to the root of the repository
+    source: String,
+    /// Newest first, never empty
+    commits: Vec<&'a Commit>,
+}
+
+impl PageHistory<'_> {
+    pub fn last_modified(&self) -> OffsetDateTime {
+        self.commits[0].timestamp
+    }
+
+    pub fn first_commit(&self) -> &Commit {
+        self.commits.last().unwrap()
+    }
+
+    pub fn markup(&self, config: &SiteConfig) -> Markup {
+        let last = self.commits[0];
+        let link = config
+            .git
+            .history_url
+            .as_ref()
+            .map(|url| url.replace("{path}", &self.source));
+
+        html!(
+            aside .history aria-label="history" {
+                details {
+                    summary {
+                        "Last changed "
+                        time datetime=(last.timestamp.format(&Rfc3339).unwrap()) {
+                            (last.timestamp.date())
+                        }
+                        " by " (last.author)
+                    }
+                    ol {
+                        @for commit in &self.commits {
+                            li {
+                                time datetime=(commit.timestamp.format(&Rfc3339).unwrap()) {
+                                    (commit.timestamp.date())
+                                }
+                                " " span .author { (commit.author) } ": " (commit.subject)
+                            }
+                        }
+                    }
+                }
+                @if let Some(link) = link {
+                    a href=(link) { "Full history" }
+                }
+            }
+        )
+    }
+}
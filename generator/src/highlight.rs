@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use comrak::adapters::SyntaxHighlighterAdapter;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use super::config::HighlightConfig;
+use super::css;
+
+/// The class of `pre` elements of highlighted code blocks.
+const CODE_BLOCK_CLASS: &str = "syntax-highlighting";
+
+fn syntax_set(config: &HighlightConfig, input_path: &Path) -> SyntaxSet {
+    let mut builder = SyntaxSet::load_defaults_newlines().into_builder();
+
+    if let Some(directory) = &config.syntaxes {
+        let directory = input_path.join(directory);
+        builder
+            .add_from_folder(&directory, true)
+            .unwrap_or_else(|e| panic!("cannot load syntax definitions from {directory:?}: {e}"));
+    }
+
+    builder.build()
+}
+
+/// A highlighter that emits CSS classes instead of inline styles, matching
+/// the classes of `stylesheet()`.
+pub struct Adapter {
+    syntax_set: SyntaxSet,
+}
+
+pub fn adapter(config: &HighlightConfig, input_path: &Path) -> Adapter {
+    Adapter {
+        syntax_set: syntax_set(config, input_path),
+    }
+}
+
+impl Adapter {
+    fn highlight(&self, lang: Option<&str>, code: &str) -> Result<String, syntect::Error> {
+        let syntax = lang
+            .filter(|lang| !lang.is_empty())
+            .and_then(|lang| self.syntax_set.find_syntax_by_token(lang))
+            .or_else(|| self.syntax_set.find_syntax_by_first_line(code))
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+
+        let mut generator = ClassedHTMLGenerator::new_with_class_style(
+            syntax,
+            &self.syntax_set,
+            ClassStyle::Spaced,
+        );
+        for line in LinesWithEndings::from(code) {
+            generator.parse_html_for_line_which_includes_newline(line)?;
+        }
+        Ok(generator.finalize())
+    }
+}
+
+impl SyntaxHighlighterAdapter for Adapter {
+    fn write_highlighted(
+        &self,
+        output: &mut dyn Write,
+        lang: Option<&str>,
+        code: &str,
+    ) -> io::Result<()> {
+        let html = self.highlight(lang, code).map_err(io::Error::other)?;
+        output.write_all(html.as_bytes())
+    }
+
+    fn write_pre_tag(
+        &self,
+        output: &mut dyn Write,
+        mut attributes: HashMap<String, String>,
+    ) -> io::Result<()> {
+        let class = match attributes.remove("class") {
+            Some(class) => format!("{CODE_BLOCK_CLASS} {class}"),
+            None => CODE_BLOCK_CLASS.to_owned(),
+        };
+        attributes.insert("class".to_owned(), class);
+        comrak::html::write_opening_tag(output, "pre", attributes)
+    }
+
+    fn write_code_tag(
+        &self,
+        output: &mut dyn Write,
+        attributes: HashMap<String, String>,
+    ) -> io::Result<()> {
+        comrak::html::write_opening_tag(output, "code", attributes)
+    }
+}
+
+fn theme<'a>(themes: &'a ThemeSet, name: &str) -> &'a Theme {
+    themes.themes.get(name).unwrap_or_else(|| {
+        panic!(
+            "unknown highlighting theme {name:?}, available: {}",
+            themes
+                .themes
+                .keys()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    })
+}
+
+/// Rules for the light and the dark theme, following the color scheme of the
+/// site.
+pub fn stylesheet(config: &HighlightConfig) -> String {
+    let themes = ThemeSet::load_defaults();
+
+    let rules = |name: &str, scope: &str| {
+        let source =
+            css_for_theme_with_class_style(theme(&themes, name), ClassStyle::Spaced).unwrap();
+        css::scope(&source, &format!("{scope} .{CODE_BLOCK_CLASS}"), ".code")
+            .unwrap_or_else(|e| panic!("invalid stylesheet for highlighting theme {name:?}: {e}"))
+    };
+
+    format!(
+        "@media (prefers-color-scheme: light) {{\n{}}}\n{}\
+         @media (prefers-color-scheme: dark) {{\n{}}}\n{}",
+        rules(&config.light, ":root:not([data-theme=dark])"),
+        rules(&config.light, ":root[data-theme=light]"),
+        rules(&config.dark, ":root:not([data-theme=light])"),
+        rules(&config.dark, ":root[data-theme=dark]"),
+    )
+}
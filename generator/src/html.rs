@@ -0,0 +1,341 @@
+// A minimal HTML tokenizer. It is only meant for the markup we generate
+// ourselves (maud and comrak output), not for arbitrary HTML from the web.
+
+/// Elements that never have any content or an end tag.
+pub const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is not markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub name: String,
+    /// Entity-decoded, `None` for boolean attributes
+    pub value: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Tag {
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    pub self_closing: bool,
+}
+
+impl Tag {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value.as_deref().unwrap_or(""))
+    }
+
+    pub fn is_void(&self) -> bool {
+        VOID_ELEMENTS.contains(&self.name.as_str())
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut output = format!("<{}", self.name);
+        for attribute in &self.attributes {
+            output.push(' ');
+            output.push_str(&attribute.name);
+            if let Some(value) = &attribute.value {
+                output.push_str(&format!("=\"{}\"", escape_attribute(value)));
+            }
+        }
+        output.push_str(if self.self_closing { "/>" } else { ">" });
+        output
+    }
+}
+
+#[derive(Debug)]
+pub enum Token<'a> {
+    /// `<!DOCTYPE ...>` or `<?xml ...?>`, including the angle brackets
+    Declaration(&'a str),
+    /// The content of comments is dropped
+    Comment,
+    /// Raw text, entities are not decoded
+    Text(&'a str),
+    StartTag(Tag),
+    EndTag(String),
+}
+
+pub fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+pub fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => {
+                    if let Some(hex) = entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                    {
+                        u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                    } else if let Some(dec) = entity.strip_prefix('#') {
+                        dec.parse().ok().and_then(char::from_u32)
+                    } else {
+                        None
+                    }
+                }
+            };
+            c.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                output.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn is_name_char(c: u8) -> bool {
+    !c.is_ascii_whitespace() && !matches!(c, b'>' | b'/' | b'=')
+}
+
+/// Parses a start tag, `pos` pointing directly behind the `<`. Returns the tag
+/// and the position behind the closing `>`.
+fn parse_start_tag(html: &str, mut pos: usize) -> (Tag, usize) {
+    let bytes = html.as_bytes();
+
+    let start = pos;
+    while pos < bytes.len() && is_name_char(bytes[pos]) {
+        pos += 1;
+    }
+    let mut tag = Tag {
+        name: html[start..pos].to_owned(),
+        attributes: vec![],
+        self_closing: false,
+    };
+
+    loop {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+
+        match bytes.get(pos) {
+            None => return (tag, pos),
+            Some(b'>') => return (tag, pos + 1),
+            Some(b'/') => {
+                if bytes.get(pos + 1) == Some(&b'>') {
+                    tag.self_closing = true;
+                    return (tag, pos + 2);
+                }
+                pos += 1;
+                continue;
+            }
+            Some(_) => (),
+        }
+
+        let start = pos;
+        while pos < bytes.len() && is_name_char(bytes[pos]) {
+            pos += 1;
+        }
+        let name = html[start..pos].to_owned();
+
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+
+        if bytes.get(pos) != Some(&b'=') {
+            tag.attributes.push(Attribute { name, value: None });
+            continue;
+        }
+        pos += 1;
+
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+
+        let value = match bytes.get(pos) {
+            Some(quote @ (b'"' | b'\'')) => {
+                let start = pos + 1;
+                let end = html[start..]
+                    .find(*quote as char)
+                    .map_or(html.len(), |end| start + end);
+                pos = (end + 1).min(html.len());
+                &html[start..end]
+            }
+            _ => {
+                let start = pos;
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() && bytes[pos] != b'>' {
+                    pos += 1;
+                }
+                &html[start..pos]
+            }
+        };
+
+        tag.attributes.push(Attribute {
+            name,
+            value: Some(decode_entities(value)),
+        });
+    }
+}
+
+pub fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let bytes = html.as_bytes();
+    let mut tokens = vec![];
+    let mut pos = 0;
+
+    let find_from = |pos: usize, needle: &str| html[pos..].find(needle).map(|i| pos + i);
+
+    while pos < bytes.len() {
+        let rest = &html[pos..];
+        let next = bytes.get(pos + 1).copied().unwrap_or(b' ');
+
+        if rest.starts_with("<!--") {
+            let end = find_from(pos + 4, "-->").unwrap_or(html.len());
+            tokens.push(Token::Comment);
+            pos = (end + 3).min(html.len());
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = find_from(pos, ">").map_or(html.len(), |end| end + 1);
+            tokens.push(Token::Declaration(&html[pos..end]));
+            pos = end;
+        } else if rest.starts_with("</") && bytes.get(pos + 2).is_some_and(u8::is_ascii_alphabetic)
+        {
+            let end = find_from(pos, ">").unwrap_or(html.len());
+            tokens.push(Token::EndTag(html[pos + 2..end].trim().to_owned()));
+            pos = (end + 1).min(html.len());
+        } else if bytes[pos] == b'<' && next.is_ascii_alphabetic() {
+            let (tag, end) = parse_start_tag(html, pos + 1);
+            pos = end;
+
+            if RAW_TEXT_ELEMENTS.contains(&tag.name.as_str()) && !tag.self_closing {
+                let closing = format!("</{}", tag.name);
+                let end = html[pos..].find(&closing).map_or(html.len(), |i| pos + i);
+                tokens.push(Token::StartTag(tag));
+                if end > pos {
+                    tokens.push(Token::Text(&html[pos..end]));
+                }
+                pos = end;
+            } else {
+                tokens.push(Token::StartTag(tag));
+            }
+        } else {
+            // a `<` that does not start markup is treated as text
+            let first = rest.chars().next().unwrap().len_utf8();
+            let end = find_from(pos + first, "<").unwrap_or(html.len());
+            tokens.push(Token::Text(&html[pos..end]));
+            pos = end;
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A compact representation of the tokens of `html`
+    fn summary(html: &str) -> Vec<String> {
+        tokenize(html)
+            .iter()
+            .map(|token| match token {
+                Token::Declaration(declaration) => format!("decl {declaration}"),
+                Token::Comment => "comment".to_owned(),
+                Token::Text(text) => format!("text {text}"),
+                Token::StartTag(tag) => format!("start {}", tag.name),
+                Token::EndTag(name) => format!("end {name}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn non_ascii_text() {
+        assert_eq!(
+            summary("<a href=\"#fn1\">↩</a>◐ Körber"),
+            ["start a", "text ↩", "end a", "text ◐ Körber"]
+        );
+        assert_eq!(summary("€ < ü"), ["text € ", "text < ü"]);
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            summary("<!DOCTYPE html><!-- a <b> ↩ --><p>x</p><!-- unterminated"),
+            [
+                "decl <!DOCTYPE html>",
+                "comment",
+                "start p",
+                "text x",
+                "end p",
+                "comment"
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_text_elements() {
+        assert_eq!(
+            summary(
+                "<script>if (a < b && c > d) { x = \"</p>\"; }</script><style>a > b {}</style>"
+            ),
+            [
+                "start script",
+                "text if (a < b && c > d) { x = \"</p>\"; }",
+                "end script",
+                "start style",
+                "text a > b {}",
+                "end style"
+            ]
+        );
+        assert_eq!(
+            summary("<script src=\"/x.js\"></script>"),
+            ["start script", "end script"]
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let tokens = tokenize("<input type=checkbox checked value='a &amp; b' data-x = y/>");
+        let Token::StartTag(tag) = &tokens[0] else {
+            panic!("not a start tag: {tokens:?}");
+        };
+        assert_eq!(tag.name, "input");
+        assert_eq!(tag.attribute("type"), Some("checkbox"));
+        assert_eq!(tag.attribute("checked"), Some(""));
+        assert!(tag.attributes[1].value.is_none());
+        assert_eq!(tag.attribute("value"), Some("a & b"));
+        assert_eq!(tag.attribute("data-x"), Some("y/"));
+        assert!(!tag.self_closing);
+        assert_eq!(tokens.len(), 1);
+
+        let tokens = tokenize("<img src=/a.png alt=\"ü\" />");
+        let Token::StartTag(tag) = &tokens[0] else {
+            panic!("not a start tag: {tokens:?}");
+        };
+        assert_eq!(tag.attribute("src"), Some("/a.png"));
+        assert_eq!(tag.attribute("alt"), Some("ü"));
+        assert!(tag.self_closing);
+    }
+}
@@ -1,25 +1,182 @@
+use super::config::IconMode;
 use super::fs;
+use super::svg::{self, Svg};
 use super::{FileType, Page};
+use maud::{html, Markup, PreEscaped};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+const SPRITE_PATH: &str = "icons/sprite.svg";
+
+/// File extensions of supported icon formats, in order of preference.
+const EXTENSIONS: &[&str] = &["svg", "png", "jpg"];
 
 pub trait Icon {
-    fn filename(&self) -> &'static str;
+    /// The path of the icon relative to `static/icons`, without extension.
+    fn name(&self) -> &str;
+    fn filename(&self) -> &str;
     fn local_path(&self, input_dir: &Path) -> PathBuf;
     fn output_path(&self) -> String;
+
+    fn is_svg(&self) -> bool {
+        self.filename().ends_with(".svg")
+    }
+}
+
+/// An icon that is looked up by name at runtime, e.g. from data that is loaded
+/// from a file. The name may contain slashes to refer to icon sets in
+/// subdirectories of `static/icons`, like `brands/Github`.
+pub struct IconRef {
+    name: String,
+    filename: String,
+}
+
+impl IconRef {
+    pub fn new(name: &str, input_path: &Path) -> Result<Self, String> {
+        let icon_directory = input_path.join("static/icons");
+
+        if !Path::new(name)
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+        {
+            return Err(format!("invalid icon name {name:?}"));
+        }
+
+        for extension in EXTENSIONS {
+            let filename = format!("{name}.{extension}");
+            if icon_directory.join(&filename).is_file() {
+                return Ok(Self {
+                    name: name.to_owned(),
+                    filename,
+                });
+            }
+        }
+
+        let suggestions = suggestions(name, &available(&icon_directory));
+        Err(if suggestions.is_empty() {
+            format!("icon {name:?} not found in {icon_directory:?}")
+        } else {
+            format!(
+                "icon {name:?} not found in {icon_directory:?}, did you mean {}?",
+                suggestions
+                    .iter()
+                    .map(|suggestion| format!("{suggestion:?}"))
+                    .collect::<Vec<String>>()
+                    .join(" or ")
+            )
+        })
+    }
+}
+
+impl Icon for IconRef {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    fn local_path(&self, input_dir: &Path) -> PathBuf {
+        input_dir.join("static/icons/").join(&self.filename)
+    }
+
+    fn output_path(&self) -> String {
+        format!("/icons/{}", self.filename)
+    }
+}
+
+/// Names of all icons below `directory`, including subdirectories.
+fn available(directory: &Path) -> Vec<String> {
+    fn walk(directory: &Path, prefix: &str, names: &mut Vec<String>) {
+        for entry in std::fs::read_dir(directory).unwrap() {
+            let entry = entry.unwrap();
+            let filename = entry.file_name().into_string().unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                walk(&entry.path(), &format!("{prefix}{filename}/"), names);
+            } else if let Some((stem, extension)) = filename.rsplit_once('.') {
+                if EXTENSIONS.contains(&extension) {
+                    names.push(format!("{prefix}{stem}"));
+                }
+            }
+        }
+    }
+
+    let mut names = vec![];
+    walk(directory, "", &mut names);
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// The closest matches for a misspelled icon name.
+fn suggestions(name: &str, available: &[String]) -> Vec<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let basename = |name: &str| name.rsplit('/').next().unwrap().to_owned();
+
+    // also match icons of the same name in another icon set
+    let distance = |candidate: &str| {
+        let candidate = candidate.to_lowercase();
+        edit_distance(&name, &candidate)
+            .min(edit_distance(&basename(&name), &basename(&candidate)) + 1)
+    };
+
+    let mut candidates: Vec<(usize, &String)> = available
+        .iter()
+        .map(|candidate| (distance(candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort();
+
+    candidates
+        .into_iter()
+        .take(3)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// Identifier of the icon in the sprite sheet and prefix for the ids within the
+/// SVG.
+fn svg_id(name: &str) -> String {
+    name.replace('/', "-")
 }
 
 pub mod m {
     #[macro_export]
     macro_rules! icon {
-        ($name:literal, $input_path:ident) => {{
+        ($name:literal, $registry:ident) => {{
             use paste::paste;
-            use std::path::PathBuf;
+            use std::path::{Path, PathBuf};
 
             paste! {
                 struct [<Icon $name>](());
             }
 
             impl $crate::icon::Icon for paste! { [<Icon $name>] } {
+                fn name(&self) -> &'static str {
+                    $name
+                }
+
                 fn filename(&self) -> &'static str {
                     concat!($name, ".svg")
                 }
@@ -37,18 +194,8 @@ pub mod m {
                 }
             }
 
-            fn verify(icon: &paste! { [<Icon $name>] }, input_dir: &Path) -> Result<(), String> {
-                let path = icon.local_path(input_dir);
-                if !path.exists() {
-                    return Err(format!("icon {} at {path:?} does not exist", $name).into());
-                }
-                // SAFETY: not doing any multithreading
-                unsafe { $crate::SEEN_ICONS.push(icon.filename()) };
-                Ok(())
-            }
-
             let icon = Box::new(paste! { [<Icon $name>](()) });
-            verify(&icon, $input_path).unwrap();
+            $registry.record(&*icon, concat!(file!(), ":", line!()));
             icon
         }};
     }
@@ -59,47 +206,226 @@ pub enum UnusedIconFiles {
     Deny,
 }
 
-pub struct IconsUnverified;
+struct Reference {
+    name: String,
+    filename: String,
+    local_path: PathBuf,
+    output_path: String,
+    location: String,
+}
+
+fn parse_svg(path: &Path, name: &str) -> Result<Svg, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{path:?}: {e}"))?;
+    Svg::parse(&source, name).map_err(|e| format!("{path:?}: {e}"))
+}
+
+/// Records every icon that is used during rendering, so the icon files can be
+/// verified afterwards.
+pub struct IconRegistry {
+    input_path: PathBuf,
+    mode: IconMode,
+    references: Mutex<Vec<Reference>>,
+    /// Number of inlined icons so far, every inlined copy gets its own id
+    /// prefix so ids stay unique within a page
+    inlined: AtomicUsize,
+}
+
+impl IconRegistry {
+    pub fn new(input_path: &Path, mode: IconMode) -> Self {
+        Self {
+            input_path: input_path.to_owned(),
+            mode,
+            references: Mutex::new(vec![]),
+            inlined: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn record(&self, icon: &dyn Icon, location: &str) {
+        let reference = Reference {
+            name: icon.name().to_owned(),
+            filename: icon.filename().to_owned(),
+            local_path: icon.local_path(&self.input_path),
+            output_path: icon.output_path(),
+            location: location.to_owned(),
+        };
+        self.references.lock().unwrap().push(reference);
+    }
+
+    /// Looks up an icon by name and records it like `icon!()` does.
+    #[track_caller]
+    pub fn resolve(&self, name: &str) -> Result<IconRef, String> {
+        let icon = IconRef::new(name, &self.input_path)?;
+        let location = std::panic::Location::caller();
+        self.record(&icon, &format!("{}:{}", location.file(), location.line()));
+        Ok(icon)
+    }
+
+    /// Renders the icon as configured. An empty `alt` marks the icon as
+    /// decorative.
+    pub fn markup(&self, icon: &dyn Icon, alt: &str) -> Markup {
+        match self.mode {
+            IconMode::File => html!(img src=(icon.output_path()) alt=(alt);),
+            _ if !icon.is_svg() => html!(img src=(icon.output_path()) alt=(alt);),
+            IconMode::Inline => {
+                let local_path = icon.local_path(&self.input_path);
+                // missing icons are reported by `verify_all()`
+                if !local_path.exists() {
+                    return html!();
+                }
+                let instance = self.inlined.fetch_add(1, Ordering::Relaxed);
+                let prefix = format!("{}-{instance}", svg_id(icon.name()));
+                let svg = parse_svg(&local_path, &prefix).unwrap_or_else(|e| panic!("{e}"));
+                PreEscaped(svg.to_inline(alt))
+            }
+            IconMode::Sprite => PreEscaped(format!(
+                "<svg class=\"icon\"{}><use href=\"/{SPRITE_PATH}#{}\"></use></svg>",
+                svg::accessibility_attributes(alt),
+                svg_id(icon.name())
+            )),
+        }
+    }
+
+    pub fn verify_all(self, allow_unused: UnusedIconFiles) -> IconsVerified {
+        let references = self.references.into_inner().unwrap();
+
+        let mut referenced: BTreeMap<&str, (&Reference, Vec<&str>)> = BTreeMap::new();
+        for reference in &references {
+            referenced
+                .entry(&reference.filename)
+                .or_insert_with(|| (reference, vec![]))
+                .1
+                .push(&reference.location);
+        }
+
+        let mut errors = vec![];
+
+        for (filename, (reference, locations)) in &referenced {
+            if !reference.local_path.exists() {
+                errors.push(format!(
+                    "icon {filename} at {:?} does not exist, referenced at {}",
+                    reference.local_path,
+                    locations.join(", ")
+                ));
+            }
+        }
 
-impl IconsUnverified {
-    pub fn verify_all(allow_unused: UnusedIconFiles, input_path: &Path) -> IconsVerified {
         if let UnusedIconFiles::Deny = allow_unused {
-            for local_file in std::fs::read_dir(input_path.join("static/icons"))
-                .unwrap()
-                .map(|entry| {
-                    let entry = entry.unwrap();
-                    if !entry.metadata().unwrap().is_file() {
-                        panic!("not a file: {entry:?}")
-                    }
-                    entry.file_name().into_string().unwrap()
-                })
-            {
-                // SAFETY: not doing any multithreading
-                if !unsafe { crate::SEEN_ICONS.contains(&local_file.as_str()) } {
-                    panic!("superfluous icon file {local_file:?}")
+            let icon_directory = self.input_path.join("static/icons");
+            for local_file in fs::list_files(&icon_directory).unwrap() {
+                if !referenced.contains_key(local_file.as_str()) {
+                    errors.push(format!("superfluous icon file {local_file:?}"));
                 }
             }
         }
 
-        IconsVerified(())
+        if !errors.is_empty() {
+            panic!("icon verification failed:\n{}", errors.join("\n"));
+        }
+
+        IconsVerified {
+            mode: self.mode,
+            used: referenced
+                .into_values()
+                .map(|(reference, _locations)| UsedIcon {
+                    name: reference.name.clone(),
+                    local_path: reference.local_path.clone(),
+                    output_path: reference.output_path.clone(),
+                })
+                .collect(),
+        }
     }
 }
 
-pub struct IconsVerified(());
+struct UsedIcon {
+    name: String,
+    local_path: PathBuf,
+    output_path: String,
+}
+
+pub struct IconsVerified {
+    mode: IconMode,
+    used: Vec<UsedIcon>,
+}
 
 impl IconsVerified {
     pub fn copy_all(self, output_base_path: &Path, input_path: &Path) -> Vec<Page> {
-        fs::copy_dir_all(
-            input_path.join("./static/icons"),
-            output_base_path,
-            Path::new("icons"),
-        )
-        .unwrap()
-        .into_iter()
-        .map(|path| Page {
-            filetype: FileType::detect(&path).unwrap(),
-            path,
-        })
-        .collect()
+        match self.mode {
+            IconMode::File => fs::copy_dir_all_with(
+                input_path.join("./static/icons"),
+                output_base_path,
+                Path::new("icons"),
+                &|path, content| {
+                    if path.extension().is_some_and(|extension| extension == "svg") {
+                        let name = path
+                            .strip_prefix(input_path.join("./static/icons"))
+                            .unwrap()
+                            .with_extension("");
+                        let source = String::from_utf8(content).map_err(std::io::Error::other)?;
+                        Ok(Some(
+                            Svg::parse(&source, &svg_id(name.to_str().unwrap()))
+                                .map_err(|e| std::io::Error::other(format!("{path:?}: {e}")))?
+                                .to_file()
+                                .into_bytes(),
+                        ))
+                    } else {
+                        Ok(Some(content))
+                    }
+                },
+            )
+            .unwrap()
+            .into_iter()
+            .map(|path| Page {
+                filetype: FileType::detect(&path).unwrap(),
+                path,
+            })
+            .collect(),
+            IconMode::Inline | IconMode::Sprite => {
+                let (svgs, others): (Vec<&UsedIcon>, Vec<&UsedIcon>) = self
+                    .used
+                    .iter()
+                    .partition(|icon| icon.local_path.extension().is_some_and(|e| e == "svg"));
+
+                // icons in other formats are still referenced as files
+                let mut pages = others
+                    .into_iter()
+                    .map(|icon| {
+                        let path = icon.output_path.trim_start_matches('/').to_owned();
+                        let destination = output_base_path.join(&path);
+                        std::fs::create_dir_all(destination.parent().unwrap()).unwrap();
+                        std::fs::copy(&icon.local_path, destination).unwrap();
+                        Page {
+                            filetype: FileType::detect(&path).unwrap(),
+                            path,
+                        }
+                    })
+                    .collect::<Vec<Page>>();
+
+                if self.mode == IconMode::Sprite {
+                    let symbols = svgs
+                        .into_iter()
+                        .map(|icon| {
+                            let id = svg_id(&icon.name);
+                            parse_svg(&icon.local_path, &id)
+                                .unwrap_or_else(|e| panic!("{e}"))
+                                .to_symbol(&id)
+                        })
+                        .collect::<Vec<String>>();
+
+                    std::fs::create_dir_all(output_base_path.join("icons")).unwrap();
+                    std::fs::write(
+                        output_base_path.join(SPRITE_PATH),
+                        svg::sprite_sheet(&symbols),
+                    )
+                    .unwrap();
+
+                    pages.push(Page {
+                        path: SPRITE_PATH.to_owned(),
+                        filetype: FileType::Svg,
+                    });
+                }
+
+                pages
+            }
+        }
     }
 }
@@ -0,0 +1,833 @@
+use std::path::Path;
+
+use super::CheckMode;
+
+const JPEG_SOI: [u8; 2] = [0xFF, 0xD8];
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const RIFF_HEADER: &[u8] = b"RIFF";
+const WEBP_HEADER: &[u8] = b"WEBP";
+
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+const XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+const TAG_ORIENTATION: u16 = 0x0112;
+const TAG_GPS_IFD: u16 = 0x8825;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Jpeg,
+    Png,
+    WebP,
+}
+
+impl Format {
+    /// Detects the format from the file content. The file extension is not
+    /// trustworthy, e.g. there are "jpg" files that are actually WebP.
+    pub fn sniff(content: &[u8]) -> Option<Self> {
+        if content.starts_with(&JPEG_SOI) {
+            Some(Self::Jpeg)
+        } else if content.starts_with(&PNG_SIGNATURE) {
+            Some(Self::Png)
+        } else if content.starts_with(RIFF_HEADER) && content.get(8..12) == Some(WEBP_HEADER) {
+            Some(Self::WebP)
+        } else {
+            None
+        }
+    }
+
+    /// Removes camera, location and other metadata, keeping only the
+    /// orientation.
+    pub fn strip_metadata(self, content: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Jpeg => strip_jpeg(content),
+            Self::Png => strip_png(content),
+            Self::WebP => strip_webp(content),
+        }
+    }
+
+    pub fn has_location(self, content: &[u8]) -> Result<bool, String> {
+        match self {
+            Self::Jpeg => jpeg_has_location(content),
+            Self::Png => png_has_location(content),
+            Self::WebP => webp_has_location(content),
+        }
+    }
+}
+
+fn u16_be(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| format!("unexpected end of data at offset {offset}"))
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("unexpected end of data at offset {offset}"))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Result<u32, String> {
+    u32_be(data, offset).map(u32::swap_bytes)
+}
+
+/// The parts of an EXIF block we care about.
+#[derive(Debug, Default)]
+struct Exif {
+    orientation: Option<u16>,
+    has_gps: bool,
+}
+
+impl Exif {
+    /// Parses IFD0 of a TIFF structure, which is what EXIF data consists of.
+    fn parse(tiff: &[u8]) -> Result<Self, String> {
+        let little_endian = match tiff.get(0..2) {
+            Some(b"II") => true,
+            Some(b"MM") => false,
+            _ => return Err("invalid TIFF byte order".into()),
+        };
+
+        let read_u16 = |offset: usize| -> Result<u16, String> {
+            let value = u16_be(tiff, offset)?;
+            Ok(if little_endian {
+                value.swap_bytes()
+            } else {
+                value
+            })
+        };
+
+        let read_u32 = |offset: usize| -> Result<u32, String> {
+            let value = u32_be(tiff, offset)?;
+            Ok(if little_endian {
+                value.swap_bytes()
+            } else {
+                value
+            })
+        };
+
+        if read_u16(2)? != 42 {
+            return Err("invalid TIFF magic".into());
+        }
+
+        let ifd = read_u32(4)? as usize;
+        let entries = read_u16(ifd)? as usize;
+
+        let mut exif = Self::default();
+        for i in 0..entries {
+            let entry = ifd + 2 + i * 12;
+            match read_u16(entry)? {
+                TAG_ORIENTATION => exif.orientation = Some(read_u16(entry + 8)?),
+                TAG_GPS_IFD => exif.has_gps = true,
+                _ => (),
+            }
+        }
+        Ok(exif)
+    }
+
+    /// Builds a TIFF structure that contains nothing but the orientation tag.
+    fn orientation_only(orientation: u16) -> Vec<u8> {
+        let mut tiff = vec![];
+        tiff.extend_from_slice(b"MM");
+        tiff.extend_from_slice(&42u16.to_be_bytes());
+        // offset of IFD0, directly after the header
+        tiff.extend_from_slice(&8u32.to_be_bytes());
+        // number of entries
+        tiff.extend_from_slice(&1u16.to_be_bytes());
+        tiff.extend_from_slice(&TAG_ORIENTATION.to_be_bytes());
+        // type SHORT
+        tiff.extend_from_slice(&3u16.to_be_bytes());
+        // count
+        tiff.extend_from_slice(&1u32.to_be_bytes());
+        // value, left-aligned in the 4 byte field
+        tiff.extend_from_slice(&orientation.to_be_bytes());
+        tiff.extend_from_slice(&[0, 0]);
+        // no next IFD
+        tiff.extend_from_slice(&0u32.to_be_bytes());
+        tiff
+    }
+}
+
+/// XMP is plain XML, so a substring search is good enough to find location
+/// properties like `exif:GPSLatitude`.
+fn xmp_has_location(xmp: &[u8]) -> bool {
+    xmp.windows(3).any(|w| w == b"GPS")
+}
+
+struct JpegSegment<'a> {
+    marker: u8,
+    data: &'a [u8],
+}
+
+/// Markers without a length field and without any data.
+fn jpeg_is_standalone(marker: u8) -> bool {
+    matches!(marker, 0x01 | 0xD0..=0xD7)
+}
+
+/// Splits a JPEG into its header segments and the entropy-coded rest, starting
+/// at the SOS marker.
+fn jpeg_segments(content: &[u8]) -> Result<(Vec<JpegSegment<'_>>, &[u8]), String> {
+    let mut segments = vec![];
+    let mut offset = JPEG_SOI.len();
+
+    loop {
+        if content.get(offset) != Some(&0xFF) {
+            return Err(format!("expected JPEG marker at offset {offset}"));
+        }
+        let marker = *content
+            .get(offset + 1)
+            .ok_or("unexpected end of JPEG data")?;
+
+        match marker {
+            // fill bytes
+            0xFF => offset += 1,
+            // start of scan, everything afterwards is image data
+            0xDA => return Ok((segments, &content[offset..])),
+            marker if jpeg_is_standalone(marker) => {
+                segments.push(JpegSegment { marker, data: &[] });
+                offset += 2;
+            }
+            _ => {
+                let length = u16_be(content, offset + 2)? as usize;
+                let data = content
+                    .get(offset + 4..offset + 2 + length)
+                    .ok_or("JPEG segment exceeds file length")?;
+                segments.push(JpegSegment { marker, data });
+                offset += 2 + length;
+            }
+        }
+    }
+}
+
+const JPEG_APP1: u8 = 0xE1;
+const JPEG_APP13: u8 = 0xED;
+const JPEG_COM: u8 = 0xFE;
+
+fn strip_jpeg(content: &[u8]) -> Result<Vec<u8>, String> {
+    let (segments, scan) = jpeg_segments(content)?;
+
+    let mut output = Vec::with_capacity(content.len());
+    output.extend_from_slice(&JPEG_SOI);
+
+    for segment in segments {
+        match segment.marker {
+            JPEG_APP1 => {
+                // EXIF and XMP. We drop everything except the orientation, as
+                // the picture would be displayed rotated otherwise.
+                if let Some(tiff) = segment.data.strip_prefix(EXIF_HEADER) {
+                    if let Some(orientation) = Exif::parse(tiff)?.orientation {
+                        let mut data = EXIF_HEADER.to_vec();
+                        data.append(&mut Exif::orientation_only(orientation));
+
+                        output.extend_from_slice(&[0xFF, JPEG_APP1]);
+                        output.extend_from_slice(&(data.len() as u16 + 2).to_be_bytes());
+                        output.append(&mut data);
+                    }
+                }
+            }
+            // IPTC and comments
+            JPEG_APP13 | JPEG_COM => (),
+            marker => {
+                output.extend_from_slice(&[0xFF, marker]);
+                if !jpeg_is_standalone(marker) {
+                    output.extend_from_slice(&(segment.data.len() as u16 + 2).to_be_bytes());
+                    output.extend_from_slice(segment.data);
+                }
+            }
+        }
+    }
+
+    output.extend_from_slice(scan);
+    Ok(output)
+}
+
+fn jpeg_has_location(content: &[u8]) -> Result<bool, String> {
+    let (segments, _scan) = jpeg_segments(content)?;
+
+    for segment in segments.iter().filter(|s| s.marker == JPEG_APP1) {
+        if let Some(tiff) = segment.data.strip_prefix(EXIF_HEADER) {
+            if Exif::parse(tiff)?.has_gps {
+                return Ok(true);
+            }
+        } else if let Some(xmp) = segment.data.strip_prefix(XMP_HEADER) {
+            if xmp_has_location(xmp) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+struct PngChunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+    raw: &'a [u8],
+}
+
+fn png_chunks(content: &[u8]) -> Result<Vec<PngChunk<'_>>, String> {
+    let mut chunks = vec![];
+    let mut offset = PNG_SIGNATURE.len();
+
+    while offset < content.len() {
+        let length = u32_be(content, offset)? as usize;
+        // length, type, data, crc
+        let raw = content
+            .get(offset..offset + 4 + 4 + length + 4)
+            .ok_or("PNG chunk exceeds file length")?;
+        chunks.push(PngChunk {
+            kind: [raw[4], raw[5], raw[6], raw[7]],
+            data: &raw[8..8 + length],
+            raw,
+        });
+        offset += raw.len();
+    }
+    Ok(chunks)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn strip_png(content: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::with_capacity(content.len());
+    output.extend_from_slice(&PNG_SIGNATURE);
+
+    for chunk in png_chunks(content)? {
+        match &chunk.kind {
+            b"eXIf" => {
+                if let Some(orientation) = Exif::parse(chunk.data)?.orientation {
+                    let mut body = b"eXIf".to_vec();
+                    body.append(&mut Exif::orientation_only(orientation));
+
+                    output.extend_from_slice(&(body.len() as u32 - 4).to_be_bytes());
+                    output.extend_from_slice(&body);
+                    output.extend_from_slice(&crc32(&body).to_be_bytes());
+                }
+            }
+            b"tEXt" | b"zTXt" | b"iTXt" | b"tIME" => (),
+            _ => output.extend_from_slice(chunk.raw),
+        }
+    }
+    Ok(output)
+}
+
+fn png_has_location(content: &[u8]) -> Result<bool, String> {
+    for chunk in png_chunks(content)? {
+        match &chunk.kind {
+            b"eXIf" if Exif::parse(chunk.data)?.has_gps => return Ok(true),
+            // XMP is stored in an iTXt chunk with keyword "XML:com.adobe.xmp"
+            b"iTXt" | b"tEXt" if xmp_has_location(chunk.data) => return Ok(true),
+            _ => (),
+        }
+    }
+    Ok(false)
+}
+
+struct WebPChunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+}
+
+const VP8X_EXIF: u8 = 0x08;
+const VP8X_XMP: u8 = 0x04;
+
+/// Splits the RIFF container of a WebP image into its chunks.
+fn webp_chunks(content: &[u8]) -> Result<Vec<WebPChunk<'_>>, String> {
+    let mut chunks = vec![];
+    let mut offset = RIFF_HEADER.len() + 4 + WEBP_HEADER.len();
+
+    while offset < content.len() {
+        let length = u32_le(content, offset + 4)? as usize;
+        let data = content
+            .get(offset + 8..offset + 8 + length)
+            .ok_or("WebP chunk exceeds file length")?;
+        chunks.push(WebPChunk {
+            kind: [
+                content[offset],
+                content[offset + 1],
+                content[offset + 2],
+                content[offset + 3],
+            ],
+            data,
+        });
+        // chunks are padded to an even length
+        offset += 8 + length + length % 2;
+    }
+    Ok(chunks)
+}
+
+/// The EXIF chunk of WebP should contain the TIFF structure directly, but
+/// some encoders keep the header of the JPEG segment.
+fn webp_exif(data: &[u8]) -> &[u8] {
+    data.strip_prefix(EXIF_HEADER).unwrap_or(data)
+}
+
+fn strip_webp(content: &[u8]) -> Result<Vec<u8>, String> {
+    let mut chunks: Vec<([u8; 4], Vec<u8>)> = vec![];
+    let mut has_exif = false;
+
+    for chunk in webp_chunks(content)? {
+        match &chunk.kind {
+            b"EXIF" => {
+                if let Some(orientation) = Exif::parse(webp_exif(chunk.data))?.orientation {
+                    chunks.push((chunk.kind, Exif::orientation_only(orientation)));
+                    has_exif = true;
+                }
+            }
+            b"XMP " => (),
+            _ => chunks.push((chunk.kind, chunk.data.to_vec())),
+        }
+    }
+
+    // the extended header announces which metadata chunks follow
+    for (kind, data) in &mut chunks {
+        if kind == b"VP8X" {
+            let flags = data.first_mut().ok_or("empty VP8X chunk")?;
+            *flags &= !VP8X_XMP;
+            if !has_exif {
+                *flags &= !VP8X_EXIF;
+            }
+        }
+    }
+
+    let mut output = Vec::with_capacity(content.len());
+    output.extend_from_slice(RIFF_HEADER);
+    // the size of the file, filled in below
+    output.extend_from_slice(&[0; 4]);
+    output.extend_from_slice(WEBP_HEADER);
+    for (kind, data) in chunks {
+        output.extend_from_slice(&kind);
+        output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        output.extend_from_slice(&data);
+        if data.len() % 2 == 1 {
+            output.push(0);
+        }
+    }
+    let size = (output.len() as u32 - 8).to_le_bytes();
+    output[4..8].copy_from_slice(&size);
+    Ok(output)
+}
+
+fn webp_has_location(content: &[u8]) -> Result<bool, String> {
+    for chunk in webp_chunks(content)? {
+        match &chunk.kind {
+            b"EXIF" if Exif::parse(webp_exif(chunk.data))?.has_gps => return Ok(true),
+            b"XMP " if xmp_has_location(chunk.data) => return Ok(true),
+            _ => (),
+        }
+    }
+    Ok(false)
+}
+
+const JPEG_DHT: u8 = 0xC4;
+const JPEG_JPG: u8 = 0xC8;
+const JPEG_DAC: u8 = 0xCC;
+
+/// Returns width and height in pixels, as the image will be displayed, i.e.
+/// respecting the EXIF orientation.
+pub fn dimensions(content: &[u8]) -> Option<(u32, u32)> {
+    let (width, height, orientation) = match Format::sniff(content)? {
+        Format::Jpeg => {
+            let (segments, _scan) = jpeg_segments(content).ok()?;
+
+            let frame = segments.iter().find(|s| {
+                matches!(s.marker, 0xC0..=0xCF)
+                    && ![JPEG_DHT, JPEG_JPG, JPEG_DAC].contains(&s.marker)
+            })?;
+
+            let orientation = segments
+                .iter()
+                .filter(|s| s.marker == JPEG_APP1)
+                .find_map(|s| s.data.strip_prefix(EXIF_HEADER))
+                .and_then(|tiff| Exif::parse(tiff).ok())
+                .and_then(|exif| exif.orientation);
+
+            // precision (1 byte), height, width
+            (
+                u32::from(u16_be(frame.data, 3).ok()?),
+                u32::from(u16_be(frame.data, 1).ok()?),
+                orientation,
+            )
+        }
+        Format::Png => {
+            // IHDR is always the first chunk
+            if content.get(12..16)? != b"IHDR" {
+                return None;
+            }
+            (u32_be(content, 16).ok()?, u32_be(content, 20).ok()?, None)
+        }
+        Format::WebP => {
+            let chunks = webp_chunks(content).ok()?;
+
+            let orientation = chunks
+                .iter()
+                .find(|chunk| &chunk.kind == b"EXIF")
+                .and_then(|chunk| Exif::parse(webp_exif(chunk.data)).ok())
+                .and_then(|exif| exif.orientation);
+
+            let (width, height) = chunks.iter().find_map(|chunk| {
+                let data = chunk.data;
+                match &chunk.kind {
+                    // flags (4 bytes), canvas width and height minus one, 24 bits each
+                    b"VP8X" => Some((
+                        (u32_le(data, 4).ok()? & 0xFF_FFFF) + 1,
+                        (u32_le(data, 6).ok()? >> 8) + 1,
+                    )),
+                    // frame tag and start code (6 bytes), width and height, 14 bits each
+                    b"VP8 " => {
+                        let size = u32_le(data, 6).ok()?;
+                        Some((size & 0x3FFF, size >> 16 & 0x3FFF))
+                    }
+                    // signature (1 byte), width and height minus one, 14 bits each
+                    b"VP8L" => {
+                        let size = u32_le(data, 1).ok()?;
+                        Some(((size & 0x3FFF) + 1, (size >> 14 & 0x3FFF) + 1))
+                    }
+                    _ => None,
+                }
+            })?;
+            (width, height, orientation)
+        }
+    };
+
+    // orientations 5 to 8 are rotated by 90 degrees
+    Some(if matches!(orientation, Some(5..=8)) {
+        (height, width)
+    } else {
+        (width, height)
+    })
+}
+
+/// Extensions of raster images, which may carry metadata
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "webp", "gif", "tif", "tiff", "heic", "avif",
+];
+
+/// Processes a single asset before publishing it. Only images are touched:
+/// their metadata is stripped, keeping the orientation. Images that contain
+/// location data in the first place, or whose metadata cannot be stripped,
+/// fail strict builds. In relaxed mode, the latter are left out, as they may
+/// still contain location data.
+pub fn process_asset(path: &Path, content: Vec<u8>, check_mode: &CheckMode) -> Option<Vec<u8>> {
+    let Some(format) = Format::sniff(&content) else {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            check_mode.report(format!(
+                "{path:?}: cannot strip metadata of an unsupported image format, leaving it out"
+            ));
+            return None;
+        }
+        return Some(content);
+    };
+
+    let stripped = format.has_location(&content).and_then(|has_location| {
+        if has_location {
+            check_mode.report(format!(
+                "{path:?} contains location metadata, it should be removed from the source as well"
+            ));
+        }
+        format.strip_metadata(&content)
+    });
+
+    match stripped {
+        Ok(stripped) => Some(stripped),
+        Err(e) => {
+            check_mode.report(format!(
+                "{path:?}: cannot strip metadata, leaving it out: {e}"
+            ));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EXIF data with the given orientation and a GPS IFD, which is empty.
+    fn tiff(orientation: Option<u16>, gps: bool) -> Vec<u8> {
+        let mut entries: Vec<[u8; 12]> = vec![];
+        if let Some(orientation) = orientation {
+            let mut entry = [0; 12];
+            entry[0..2].copy_from_slice(&TAG_ORIENTATION.to_le_bytes());
+            entry[2..4].copy_from_slice(&3u16.to_le_bytes());
+            entry[4..8].copy_from_slice(&1u32.to_le_bytes());
+            entry[8..10].copy_from_slice(&orientation.to_le_bytes());
+            entries.push(entry);
+        }
+        if gps {
+            let mut entry = [0; 12];
+            entry[0..2].copy_from_slice(&TAG_GPS_IFD.to_le_bytes());
+            entry[2..4].copy_from_slice(&4u16.to_le_bytes());
+            entry[4..8].copy_from_slice(&1u32.to_le_bytes());
+            // the GPS IFD follows IFD0
+            entry[8..12].copy_from_slice(&(8 + 2 + 12 * 2 + 4u32).to_le_bytes());
+            entries.push(entry);
+        }
+
+        let mut tiff = b"II".to_vec();
+        tiff.extend_from_slice(&42u16.to_le_bytes());
+        tiff.extend_from_slice(&8u32.to_le_bytes());
+        tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for entry in entries {
+            tiff.extend_from_slice(&entry);
+        }
+        tiff.extend_from_slice(&0u32.to_le_bytes());
+        // the GPS IFD, without any entries
+        tiff.extend_from_slice(&[0; 6]);
+        tiff
+    }
+
+    fn xmp() -> Vec<u8> {
+        br#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description exif:GPSLatitude="48,5N"/></x:xmpmeta>"#.to_vec()
+    }
+
+    fn jpeg(segments: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut jpeg = JPEG_SOI.to_vec();
+        for (marker, data) in segments {
+            jpeg.extend_from_slice(&[0xFF, *marker]);
+            jpeg.extend_from_slice(&(data.len() as u16 + 2).to_be_bytes());
+            jpeg.extend_from_slice(data);
+        }
+        // start of scan, image data and end of image
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02, 0x12, 0x34, 0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn png(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (kind, data) in chunks {
+            let body = [kind.as_slice(), data].concat();
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(&body);
+            png.extend_from_slice(&crc32(&body).to_be_bytes());
+        }
+        png
+    }
+
+    fn webp(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut webp = RIFF_HEADER.to_vec();
+        webp.extend_from_slice(&[0; 4]);
+        webp.extend_from_slice(WEBP_HEADER);
+        for (kind, data) in chunks {
+            webp.extend_from_slice(*kind);
+            webp.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            webp.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                webp.push(0);
+            }
+        }
+        let size = (webp.len() as u32 - 8).to_le_bytes();
+        webp[4..8].copy_from_slice(&size);
+        webp
+    }
+
+    fn exif_segment(orientation: Option<u16>, gps: bool) -> (u8, Vec<u8>) {
+        (JPEG_APP1, [EXIF_HEADER, &tiff(orientation, gps)].concat())
+    }
+
+    #[test]
+    fn exif() {
+        let exif = Exif::parse(&tiff(Some(6), true)).unwrap();
+        assert_eq!(exif.orientation, Some(6));
+        assert!(exif.has_gps);
+
+        let exif = Exif::parse(&Exif::orientation_only(3)).unwrap();
+        assert_eq!(exif.orientation, Some(3));
+        assert!(!exif.has_gps);
+
+        assert!(Exif::parse(b"XX").is_err());
+        assert!(Exif::parse(&tiff(Some(1), false)[..12]).is_err());
+    }
+
+    #[test]
+    fn jpeg_location() {
+        let original = jpeg(&[
+            (0xE0, b"JFIF\0".to_vec()),
+            exif_segment(Some(6), true),
+            (JPEG_APP1, [XMP_HEADER, &xmp()].concat()),
+            (JPEG_COM, b"taken at home".to_vec()),
+        ]);
+        assert_eq!(Format::sniff(&original), Some(Format::Jpeg));
+        assert!(Format::Jpeg.has_location(&original).unwrap());
+
+        let stripped = Format::Jpeg.strip_metadata(&original).unwrap();
+        assert!(!Format::Jpeg.has_location(&stripped).unwrap());
+        assert_eq!(
+            stripped,
+            jpeg(&[
+                (0xE0, b"JFIF\0".to_vec()),
+                (
+                    JPEG_APP1,
+                    [EXIF_HEADER, &Exif::orientation_only(6)].concat()
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn jpeg_xmp_location() {
+        let original = jpeg(&[(JPEG_APP1, [XMP_HEADER, &xmp()].concat())]);
+        assert!(Format::Jpeg.has_location(&original).unwrap());
+
+        let stripped = Format::Jpeg.strip_metadata(&original).unwrap();
+        assert_eq!(stripped, jpeg(&[]));
+    }
+
+    #[test]
+    fn jpeg_without_location() {
+        let original = jpeg(&[exif_segment(None, false)]);
+        assert!(!Format::Jpeg.has_location(&original).unwrap());
+        assert_eq!(Format::Jpeg.strip_metadata(&original).unwrap(), jpeg(&[]));
+    }
+
+    #[test]
+    fn png_location() {
+        let header = vec![0, 0, 0, 2, 0, 0, 0, 1, 8, 6, 0, 0, 0];
+        let original = png(&[
+            (b"IHDR", header.clone()),
+            (b"eXIf", tiff(Some(8), true)),
+            (
+                b"iTXt",
+                [b"XML:com.adobe.xmp\0\0\0\0\0".as_slice(), &xmp()].concat(),
+            ),
+            (b"tEXt", b"Comment\0taken at home".to_vec()),
+            (b"IDAT", vec![1, 2, 3]),
+            (b"IEND", vec![]),
+        ]);
+        assert_eq!(Format::sniff(&original), Some(Format::Png));
+        assert!(Format::Png.has_location(&original).unwrap());
+
+        let stripped = Format::Png.strip_metadata(&original).unwrap();
+        assert!(!Format::Png.has_location(&stripped).unwrap());
+        assert_eq!(
+            stripped,
+            png(&[
+                (b"IHDR", header),
+                (b"eXIf", Exif::orientation_only(8)),
+                (b"IDAT", vec![1, 2, 3]),
+                (b"IEND", vec![]),
+            ])
+        );
+    }
+
+    #[test]
+    fn webp_location() {
+        let original = webp(&[
+            (
+                b"VP8X",
+                vec![VP8X_EXIF | VP8X_XMP, 0, 0, 0, 1, 0, 0, 1, 0, 0],
+            ),
+            (b"VP8L", vec![0x2F, 1, 2, 3, 4]),
+            (b"EXIF", tiff(Some(3), true)),
+            (b"XMP ", xmp()),
+        ]);
+        assert_eq!(Format::sniff(&original), Some(Format::WebP));
+        assert!(Format::WebP.has_location(&original).unwrap());
+
+        let stripped = Format::WebP.strip_metadata(&original).unwrap();
+        assert!(!Format::WebP.has_location(&stripped).unwrap());
+        assert_eq!(
+            stripped,
+            webp(&[
+                (b"VP8X", vec![VP8X_EXIF, 0, 0, 0, 1, 0, 0, 1, 0, 0]),
+                (b"VP8L", vec![0x2F, 1, 2, 3, 4]),
+                (b"EXIF", Exif::orientation_only(3)),
+            ])
+        );
+
+        let original = webp(&[
+            (b"VP8X", vec![VP8X_EXIF, 0, 0, 0, 1, 0, 0, 1, 0, 0]),
+            (b"VP8 ", vec![1, 2, 3]),
+            (b"EXIF", [EXIF_HEADER, &tiff(None, true)].concat()),
+        ]);
+        assert!(Format::WebP.has_location(&original).unwrap());
+        assert_eq!(
+            Format::WebP.strip_metadata(&original).unwrap(),
+            webp(&[
+                (b"VP8X", vec![0, 0, 0, 0, 1, 0, 0, 1, 0, 0]),
+                (b"VP8 ", vec![1, 2, 3]),
+            ])
+        );
+    }
+
+    #[test]
+    fn dimensions_of_formats() {
+        // precision, height, width, components
+        let frame = vec![8, 0, 2, 0, 3, 1, 1, 0x11, 0];
+        assert_eq!(dimensions(&jpeg(&[(0xC0, frame.clone())])), Some((3, 2)));
+        assert_eq!(
+            dimensions(&jpeg(&[exif_segment(Some(6), false), (0xC2, frame)])),
+            Some((2, 3))
+        );
+
+        let header = vec![0, 0, 1, 0, 0, 0, 0, 20, 8, 6, 0, 0, 0];
+        assert_eq!(dimensions(&png(&[(b"IHDR", header)])), Some((256, 20)));
+
+        let vp8x = vec![0, 0, 0, 0, 0xFF, 0x01, 0, 0x63, 0, 0];
+        assert_eq!(dimensions(&webp(&[(b"VP8X", vp8x)])), Some((512, 100)));
+        let vp8 = vec![0, 0, 0, 0x9D, 0x01, 0x2A, 0x20, 0x03, 0x58, 0x02];
+        assert_eq!(dimensions(&webp(&[(b"VP8 ", vp8)])), Some((800, 600)));
+        // width 4 and height 2, minus one each
+        let vp8l = vec![0x2F, 0x03, 0x40, 0x00, 0x00];
+        assert_eq!(dimensions(&webp(&[(b"VP8L", vp8l)])), Some((4, 2)));
+
+        assert_eq!(dimensions(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn truncated() {
+        let original = jpeg(&[exif_segment(Some(1), true)]);
+        assert!(Format::Jpeg.has_location(&original[..20]).is_err());
+
+        let original = png(&[(b"IHDR", vec![0; 13]), (b"IEND", vec![])]);
+        assert!(Format::Png.strip_metadata(&original[..20]).is_err());
+    }
+
+    #[test]
+    fn crc() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn assets() {
+        let process = |path: &str, content: &[u8]| {
+            process_asset(Path::new(path), content.to_vec(), &CheckMode::Relaxed)
+        };
+
+        let original = jpeg(&[exif_segment(None, false)]);
+        assert_eq!(process("a.jpg", &original), Some(jpeg(&[])));
+        // the content decides how an image is stripped, not the extension
+        assert_eq!(process("a.png", &original), Some(jpeg(&[])));
+        assert_eq!(process("a.txt", b"text"), Some(b"text".to_vec()));
+
+        assert_eq!(process("a.jpg", &original[..20]), None);
+        assert_eq!(process("a.GIF", b"GIF89a"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "contains location metadata")]
+    fn strict_location() {
+        let original = jpeg(&[exif_segment(None, true)]);
+        process_asset(Path::new("a.jpg"), original, &CheckMode::Strict);
+    }
+
+    #[test]
+    #[should_panic(expected = "unsupported image format")]
+    fn strict_unsupported() {
+        process_asset(Path::new("a.gif"), b"GIF89a".to_vec(), &CheckMode::Strict);
+    }
+}
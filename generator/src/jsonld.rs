@@ -0,0 +1,355 @@
+use maud::{html, Markup, PreEscaped};
+use serde_json::{json, Value};
+use time::format_description::well_known::Rfc3339;
+
+use super::blog;
+use super::config::SiteConfig;
+use super::data;
+use super::meta::Metadata;
+
+/// The properties every object of a type has to have. Other types are not
+/// emitted.
+const REQUIRED: &[(&str, &[&str])] = &[
+    (
+        "BlogPosting",
+        &[
+            "headline",
+            "description",
+            "datePublished",
+            "author",
+            "url",
+            "image",
+        ],
+    ),
+    ("Person", &["name", "url"]),
+    ("WebSite", &["name", "url"]),
+    (
+        "SoftwareSourceCode",
+        &["name", "description", "codeRepository", "url"],
+    ),
+    ("EducationalOccupationalCredential", &["name", "url"]),
+];
+
+/// Properties that have to be absolute URLs.
+const URL_PROPERTIES: &[&str] = &[
+    "url",
+    "image",
+    "codeRepository",
+    "sameAs",
+    "mainEntityOfPage",
+];
+
+fn author(config: &SiteConfig) -> Value {
+    json!({
+        "@type": "Person",
+        "name": config.author,
+        "url": config.url("/"),
+    })
+}
+
+pub fn person(config: &SiteConfig) -> Value {
+    let mut person = author(config);
+
+    let same_as: Vec<&str> = config
+        .footer
+        .socials
+        .iter()
+        .map(|social| social.link.as_str())
+        .filter(|link| link.starts_with("https://") || link.starts_with("http://"))
+        .collect();
+    person["sameAs"] = json!(same_as);
+
+    if let Some(email) = config
+        .footer
+        .socials
+        .iter()
+        .find_map(|social| social.link.strip_prefix("mailto:"))
+    {
+        person["email"] = json!(email);
+    }
+
+    person["image"] = json!(config.url(&config.landing.image));
+
+    let credentials: Vec<Value> = data::certifications()
+        .iter()
+        .map(|certification| {
+            json!({
+                "@type": "EducationalOccupationalCredential",
+                "name": certification.title,
+                "url": certification.link,
+            })
+        })
+        .collect();
+    person["hasCredential"] = json!(credentials);
+
+    person
+}
+
+pub fn website(config: &SiteConfig) -> Value {
+    json!({
+        "@type": "WebSite",
+        "name": config.title,
+        "url": config.url("/"),
+        "description": config.description,
+        "inLanguage": config.language,
+        "author": author(config),
+    })
+}
+
+pub fn project(project: &data::Project, config: &SiteConfig) -> Value {
+    json!({
+        "@type": "SoftwareSourceCode",
+        "name": project.title,
+        "description": project.description.join(" "),
+        "codeRepository": project.links.github,
+        "url": project.links.homepage.unwrap_or(project.links.github),
+        "programmingLanguage": project.tags.languages,
+        "keywords": project.tags.tech,
+        "author": author(config),
+    })
+}
+
+/// `None` for pages that are not blog posts.
+pub fn blog_posting(meta: &Metadata, config: &SiteConfig) -> Option<Value> {
+    let article = meta.article.as_ref()?;
+    let url = config.url(meta.path);
+    let mut posting = json!({
+        "@type": "BlogPosting",
+        "headline": meta.title,
+        "description": meta.description,
+        "datePublished": article.published.format(&Rfc3339).unwrap(),
+        "author": author(config),
+        "url": url,
+        "mainEntityOfPage": url,
+        "image": config.url(meta.image.unwrap_or(&config.image)),
+        "keywords": article.tags,
+        "inLanguage": config.language,
+        "wordCount": article.words,
+        "timeRequired": format!("PT{}M", blog::reading_time(article.words)),
+    });
+    if let Some(modified) = meta.modified() {
+        posting["dateModified"] = json!(modified.format(&Rfc3339).unwrap());
+    }
+    Some(posting)
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    }
+}
+
+fn is_absolute_url(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.starts_with("https://") || s.starts_with("http://"),
+        Value::Array(a) => a.iter().all(is_absolute_url),
+        _ => false,
+    }
+}
+
+/// Checks that all objects have a known type and all properties required for
+/// it, recursively.
+fn validate(value: &Value, path: &str, errors: &mut Vec<String>) {
+    match value {
+        Value::Object(object) => {
+            if let Some(kind) = object.get("@type") {
+                let kind = kind.as_str().unwrap_or_default();
+                match REQUIRED.iter().find(|(name, _)| *name == kind) {
+                    Some((_, required)) => {
+                        for property in *required {
+                            match object.get(*property) {
+                                Some(value) if !is_empty(value) => (),
+                                _ => errors.push(format!("{path}: {kind} is missing {property}")),
+                            }
+                        }
+                    }
+                    None => errors.push(format!("{path}: unknown type {kind:?}")),
+                }
+            }
+
+            for (property, value) in object {
+                if URL_PROPERTIES.contains(&property.as_str()) && !is_absolute_url(value) {
+                    errors.push(format!("{path}.{property}: {value} is not an absolute URL"));
+                }
+                validate(value, &format!("{path}.{property}"), errors);
+            }
+        }
+        Value::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                validate(value, &format!("{path}[{i}]"), errors);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// A `script` element containing `value`, which is validated first.
+pub fn markup(mut value: Value) -> Markup {
+    let mut errors = vec![];
+    if value.get("@type").is_none() {
+        errors.push("$: missing @type".to_owned());
+    }
+    validate(&value, "$", &mut errors);
+    if !errors.is_empty() {
+        panic!("invalid structured data:\n{}\n{value:#}", errors.join("\n"));
+    }
+
+    value["@context"] = json!("https://schema.org");
+
+    // "</script>" in a string must not end the element
+    let content = value.to_string().replace("</", "<\\/");
+    html!(script type="application/ld+json" { (PreEscaped(content)) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::Article;
+
+    const CONFIG: &str = r#"
+title = "Example"
+author = "Jane Doe"
+base_url = "https://example.com"
+language = "en"
+description = "An example site"
+image = "/assets/profile.jpg"
+navigation = []
+
+[landing]
+heading = "Hi!"
+introduction = "Hello"
+image = "/assets/profile.jpg"
+
+[blog]
+path = "blog"
+
+[footer]
+socials = [
+  { name = "Github", link = "https://github.com/example", icon = "Github" },
+  { name = "E-Mail", link = "mailto:jane@example.com", icon = "Email" },
+]
+
+[theme]
+palette = "default"
+palettes = {}
+
+[css]
+stylesheets = []
+
+[highlighting]
+light = "InspiredGitHub"
+dark = "base16-ocean.dark"
+
+[html]
+"#;
+
+    fn config() -> SiteConfig {
+        toml::from_str(CONFIG).unwrap()
+    }
+
+    fn errors(value: &Value) -> Vec<String> {
+        let mut errors = vec![];
+        validate(value, "$", &mut errors);
+        errors
+    }
+
+    /// `value` is valid, but not without any of the properties its type
+    /// requires.
+    fn assert_complete(value: Value) {
+        assert_eq!(errors(&value), Vec::<String>::new());
+
+        let kind = value["@type"].as_str().unwrap();
+        let (_, required) = REQUIRED.iter().find(|(name, _)| *name == kind).unwrap();
+        for property in *required {
+            let mut incomplete = value.clone();
+            incomplete.as_object_mut().unwrap().remove(*property);
+            let errors = errors(&incomplete);
+            assert!(
+                errors.contains(&format!("$: {kind} is missing {property}")),
+                "{kind} without {property}: {errors:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn blog_posting() {
+        let config = config();
+        let meta = Metadata {
+            article: Some(Article {
+                published: time::OffsetDateTime::UNIX_EPOCH,
+                modified: None,
+                tags: vec!["rust"],
+                words: 450,
+            }),
+            description: "A post".to_owned(),
+            ..Metadata::page("Post", "blog/post.html", &config)
+        };
+
+        let posting = super::blog_posting(&meta, &config).unwrap();
+        assert_eq!(posting["url"], "https://example.com/blog/post.html");
+        assert_eq!(posting["image"], "https://example.com/assets/profile.jpg");
+        assert_eq!(posting["timeRequired"], "PT3M");
+        assert_complete(posting);
+
+        let page = Metadata::page("Page", "index.html", &config);
+        assert!(super::blog_posting(&page, &config).is_none());
+    }
+
+    #[test]
+    fn person() {
+        let person = super::person(&config());
+        assert_eq!(person["email"], "jane@example.com");
+        assert_eq!(person["sameAs"], json!(["https://github.com/example"]));
+        assert_complete(person);
+    }
+
+    #[test]
+    fn website() {
+        assert_complete(super::website(&config()));
+    }
+
+    #[test]
+    fn project() {
+        let project = data::Project {
+            title: "Example",
+            figure: None,
+            description: vec!["An example", "project"],
+            tags: data::ProjectTags {
+                languages: vec!["Rust"],
+                tech: vec![],
+            },
+            links: data::ProjectLinks {
+                github: "https://github.com/example/example",
+                homepage: None,
+            },
+        };
+        assert_complete(super::project(&project, &config()));
+    }
+
+    #[test]
+    fn invalid() {
+        let config = config();
+
+        let mut person = super::person(&config);
+        person["url"] = json!("/about/index.html");
+        assert_eq!(
+            errors(&person),
+            ["$.url: \"/about/index.html\" is not an absolute URL"]
+        );
+
+        let mut website = super::website(&config);
+        website["author"]["@type"] = json!("Organization");
+        assert_eq!(
+            errors(&website),
+            ["$.author: unknown type \"Organization\""]
+        );
+
+        let mut website = super::website(&config);
+        website["name"] = json!("");
+        assert_eq!(errors(&website), ["$: WebSite is missing name"]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use super::html::{self, Token};
+use super::{CheckMode, FileType, Page};
+
+const LINK_ATTRIBUTES: &[&str] = &["href", "src"];
+
+pub fn is_external(url: &str) -> bool {
+    if url.starts_with("//") {
+        return true;
+    }
+    match url.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                output.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        output.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Resolves `url` relative to the page at `page_path` into a path relative to
+/// the build directory, as used in the manifest.
+fn resolve(page_path: &str, url: &str) -> Result<String, String> {
+    let url = percent_decode(url);
+
+    let mut resolved = if url.starts_with('/') {
+        PathBuf::new()
+    } else {
+        Path::new(page_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default()
+    };
+
+    for component in Path::new(&url).components() {
+        match component {
+            Component::Normal(name) => resolved.push(name),
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return Err("points outside of the site".into());
+                }
+            }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => (),
+        }
+    }
+
+    // the server redirects the root to the landing page
+    if resolved.as_os_str().is_empty() {
+        resolved.push("index.html");
+    }
+
+    Ok(resolved.to_str().unwrap().to_owned())
+}
+
+struct HtmlPage {
+    links: Vec<String>,
+    ids: HashSet<String>,
+}
+
+impl HtmlPage {
+    fn parse(content: &str) -> Self {
+        let mut page = Self {
+            links: vec![],
+            ids: HashSet::new(),
+        };
+
+        for token in html::tokenize(content) {
+            let Token::StartTag(tag) = token else {
+                continue;
+            };
+
+            for attribute in LINK_ATTRIBUTES {
+                if let Some(link) = tag.attribute(attribute) {
+                    page.links.push(link.to_owned());
+                }
+            }
+
+            if let Some(id) = tag.attribute("id") {
+                page.ids.insert(id.to_owned());
+            }
+            if tag.name == "a" {
+                if let Some(name) = tag.attribute("name") {
+                    page.ids.insert(name.to_owned());
+                }
+            }
+        }
+
+        page
+    }
+}
+
+/// Checks that all internal links of all rendered HTML pages point to a page in
+/// the manifest and, if they contain a fragment, to an existing id on that page.
+///
+/// Absolute links to the site itself, like canonical URLs, are checked like
+/// internal ones.
+///
+/// Returns all external HTTP links together with the pages linking to them, as
+/// these cannot be checked here.
+pub fn check(
+    build_directory: &Path,
+    pages: &[Page],
+    base_url: &str,
+    check_mode: &CheckMode,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let html_pages: HashMap<&str, HtmlPage> = pages
+        .iter()
+        .filter(|page| matches!(page.filetype, FileType::Html))
+        .map(|page| {
+            let content = std::fs::read_to_string(build_directory.join(&page.path)).unwrap();
+            (page.path.as_str(), HtmlPage::parse(&content))
+        })
+        .collect();
+
+    let known_paths: HashSet<&str> = pages.iter().map(|page| page.path.as_str()).collect();
+
+    let mut errors = vec![];
+    let mut external_links: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for (path, page) in &html_pages {
+        for link in &page.links {
+            if link.is_empty() {
+                continue;
+            }
+
+            let link = &match link.strip_prefix(base_url) {
+                Some(local) if local.starts_with('/') => local.to_owned(),
+                _ => link.clone(),
+            };
+
+            if is_external(link) {
+                if link.starts_with("http://") || link.starts_with("https://") {
+                    external_links
+                        .entry(link.clone())
+                        .or_default()
+                        .insert(path.to_string());
+                }
+                continue;
+            }
+
+            let (target, fragment) = match link.split_once('#') {
+                Some((target, fragment)) => (target, Some(fragment)),
+                None => (link.as_str(), None),
+            };
+            let target = target.split('?').next().unwrap();
+
+            let target = if target.is_empty() {
+                path.to_string()
+            } else {
+                match resolve(path, target) {
+                    Ok(target) => target,
+                    Err(e) => {
+                        errors.push(format!("{path}: broken link {link:?}: {e}"));
+                        continue;
+                    }
+                }
+            };
+
+            if !known_paths.contains(target.as_str()) {
+                errors.push(format!(
+                    "{path}: broken link {link:?}: {target} does not exist"
+                ));
+                continue;
+            }
+
+            if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+                if let Some(target_page) = html_pages.get(target.as_str()) {
+                    if !target_page.ids.contains(&percent_decode(fragment)) {
+                        errors.push(format!(
+                            "{path}: broken link {link:?}: no element with id {fragment:?} in {target}"
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    errors.sort();
+
+    if !errors.is_empty() {
+        check_mode.report(format!("broken links found:\n{}", errors.join("\n")));
+    }
+
+    external_links
+}
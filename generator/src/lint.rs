@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::html::{self, Tag, Token};
+use super::{CheckMode, FileType, Page};
+
+/// Elements that must not appear inside a `p`, as they implicitly close it.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// Elements whose end tag may be left out, e.g. by the minifier.
+const OPTIONAL_END_TAGS: &[&str] = &[
+    "html", "head", "body", "li", "dt", "dd", "td", "th", "tr", "thead", "tbody", "tfoot", "option",
+];
+
+/// Whether the start tag `start` implicitly closes the open element `open`.
+fn implicitly_closes(open: &str, start: &str) -> bool {
+    match open {
+        "head" => start == "body",
+        "li" => start == "li",
+        "dt" | "dd" => matches!(start, "dt" | "dd"),
+        "td" | "th" => matches!(start, "td" | "th" | "tr" | "tbody" | "tfoot"),
+        "tr" => matches!(start, "tr" | "tbody" | "tfoot"),
+        "thead" | "tbody" => matches!(start, "tbody" | "tfoot"),
+        "option" => matches!(start, "option" | "optgroup"),
+        _ => false,
+    }
+}
+
+struct OpenElement {
+    name: String,
+    /// Whether the element contains text or an image with alt text
+    has_content: bool,
+    /// Whether the element has an accessible name from its attributes
+    labelled: bool,
+}
+
+#[derive(Default)]
+struct Linter {
+    findings: Vec<String>,
+    stack: Vec<OpenElement>,
+    ids: HashSet<String>,
+    last_heading: Option<u8>,
+    h1_count: usize,
+}
+
+fn heading_level(name: &str) -> Option<u8> {
+    name.strip_prefix('h')
+        .and_then(|level| level.parse().ok())
+        .filter(|level| (1..=6).contains(level))
+}
+
+impl Linter {
+    fn mark_content(&mut self) {
+        for element in &mut self.stack {
+            element.has_content = true;
+        }
+    }
+
+    fn is_inside(&self, name: &str) -> bool {
+        self.stack.iter().any(|element| element.name == name)
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        if tag.name == "img" {
+            match tag.attribute("alt") {
+                None => self.findings.push(format!(
+                    "img {:?} has no alt text",
+                    tag.attribute("src").unwrap_or_default()
+                )),
+                Some(alt) if !alt.trim().is_empty() => self.mark_content(),
+                Some(_) => (),
+            }
+        }
+
+        if tag.name == "svg"
+            && tag
+                .attribute("aria-label")
+                .is_some_and(|label| !label.trim().is_empty())
+        {
+            self.mark_content();
+        }
+
+        if let Some(id) = tag.attribute("id") {
+            if !self.ids.insert(id.to_owned()) {
+                self.findings.push(format!("duplicate id {id:?}"));
+            }
+        }
+
+        if let Some(level) = heading_level(&tag.name) {
+            match self.last_heading {
+                None if level != 1 => self
+                    .findings
+                    .push(format!("first heading is h{level} instead of h1")),
+                Some(last) if level > last + 1 => self
+                    .findings
+                    .push(format!("heading level skipped: h{level} follows h{last}")),
+                _ => (),
+            }
+            if level == 1 {
+                self.h1_count += 1;
+                if self.h1_count == 2 {
+                    self.findings.push("more than one h1".to_owned());
+                }
+            }
+            self.last_heading = Some(level);
+        }
+
+        if self.is_inside("p") && BLOCK_ELEMENTS.contains(&tag.name.as_str()) {
+            self.findings
+                .push(format!("invalid nesting: {} inside p", tag.name));
+        }
+        if tag.name == "a" && self.is_inside("a") {
+            self.findings.push("invalid nesting: a inside a".into());
+        }
+
+        while self
+            .stack
+            .last()
+            .is_some_and(|element| implicitly_closes(&element.name, &tag.name))
+        {
+            self.stack.pop();
+        }
+
+        if !tag.is_void() && !tag.self_closing {
+            let labelled = ["aria-label", "aria-labelledby", "title"]
+                .iter()
+                .any(|attribute| {
+                    tag.attribute(attribute)
+                        .is_some_and(|label| !label.trim().is_empty())
+                });
+            self.stack.push(OpenElement {
+                labelled,
+                name: tag.name,
+                has_content: false,
+            });
+        }
+    }
+
+    fn end_tag(&mut self, name: &str) {
+        let Some(position) = self.stack.iter().rposition(|element| element.name == name) else {
+            self.findings.push(format!("stray end tag {name}"));
+            return;
+        };
+
+        for element in self.stack.drain(position..).rev() {
+            if element.name != name {
+                if OPTIONAL_END_TAGS.contains(&element.name.as_str()) {
+                    continue;
+                }
+                self.findings.push(format!(
+                    "invalid nesting: {} closed by end tag {name}",
+                    element.name
+                ));
+            } else if element.name == "a" && !element.has_content && !element.labelled {
+                self.findings
+                    .push("link without discernible text".to_owned());
+            }
+        }
+    }
+
+    fn lint(mut self, content: &str) -> Vec<String> {
+        for token in html::tokenize(content) {
+            match token {
+                Token::StartTag(tag) => self.start_tag(tag),
+                Token::EndTag(name) => self.end_tag(&name),
+                Token::Text(text) => {
+                    let in_raw_text = self.stack.last().is_some_and(|element| {
+                        ["script", "style"].contains(&element.name.as_str())
+                    });
+                    if !in_raw_text && !html::decode_entities(text).trim().is_empty() {
+                        self.mark_content();
+                    }
+                }
+                Token::Declaration(_) | Token::Comment => (),
+            }
+        }
+
+        for element in &self.stack {
+            if OPTIONAL_END_TAGS.contains(&element.name.as_str()) {
+                continue;
+            }
+            self.findings
+                .push(format!("element {} is never closed", element.name));
+        }
+
+        self.findings
+    }
+}
+
+/// Checks all rendered HTML pages for common accessibility and markup problems.
+/// Relaxed mode only prints a report per page.
+pub fn check(build_directory: &Path, pages: &[Page], check_mode: &CheckMode) {
+    let mut report: Vec<(&str, Vec<String>)> = pages
+        .iter()
+        .filter(|page| matches!(page.filetype, FileType::Html))
+        .map(|page| {
+            let content = std::fs::read_to_string(build_directory.join(&page.path)).unwrap();
+            (page.path.as_str(), Linter::default().lint(&content))
+        })
+        .filter(|(_path, findings)| !findings.is_empty())
+        .collect();
+
+    if report.is_empty() {
+        return;
+    }
+
+    report.sort();
+
+    let report = report
+        .into_iter()
+        .map(|(path, findings)| {
+            format!(
+                "{path}: {} issue(s)\n{}",
+                findings.len(),
+                findings
+                    .iter()
+                    .map(|finding| format!("  {finding}"))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    check_mode.report(format!("accessibility check failed:\n{report}"));
+}
@@ -1,21 +1,36 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use comrak::plugins::syntect::SyntectAdapter;
-use comrak::{markdown_to_html_with_plugins, Options, Plugins};
-use maud::{html, Markup};
+use comrak::{format_html_with_plugins, parse_document, Arena, Options, Plugins};
+use maud::{html, Markup, PreEscaped};
 
+mod audit;
+mod blog;
+mod config;
+mod css;
 mod data;
+mod feed;
 mod fs;
+mod git;
+mod highlight;
+mod html;
 mod icon;
+mod image;
+mod jsonld;
+mod links;
+mod lint;
+mod markdown;
+mod meta;
+mod minify;
 mod pages;
 mod render;
-
-use pages::Render;
-
-static mut SEEN_ICONS: Vec<&'static str> = vec![];
+mod search;
+mod svg;
+mod theme;
+mod vendor;
 
 #[derive(Debug, Serialize)]
 enum FileType {
@@ -24,16 +39,27 @@ enum FileType {
     Css,
     Jpg,
     Png,
+    Gif,
+    Webp,
+    Js,
+    Json,
+    Xml,
 }
 
 impl FileType {
     fn detect(path: &str) -> Result<Self, String> {
         let path = Path::new(path);
-        let ext = path.extension().unwrap().to_str().unwrap();
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
         Ok(match ext {
-            "jpg" => FileType::Jpg,
+            "jpg" | "jpeg" => FileType::Jpg,
             "svg" => FileType::Svg,
             "png" => FileType::Png,
+            "gif" => FileType::Gif,
+            "webp" => FileType::Webp,
+            "js" => FileType::Js,
             _ => return Err(format!("unknown extension: {ext}")),
         })
     }
@@ -49,12 +75,21 @@ pub struct Page {
 struct Manifest {
     content_directory: PathBuf,
     pages: Vec<Page>,
+    /// Relative to the manifest
+    search_documents: PathBuf,
 }
 
-#[allow(dead_code)]
 #[derive(Deserialize, Clone)]
 struct Tag(String);
 
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+struct Change {
+    #[serde(with = "time::serde::rfc3339")]
+    date: time::OffsetDateTime,
+    description: String,
+}
+
 #[derive(Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 struct Frontmatter {
@@ -62,57 +97,92 @@ struct Frontmatter {
     #[serde(with = "time::serde::rfc3339", rename = "date")]
     timestamp: time::OffsetDateTime,
     summary: String,
-    #[allow(dead_code)]
     tags: Vec<Tag>,
+    /// Shown in link previews, an absolute path below `static/` or a URL
+    #[serde(default)]
+    image: Option<String>,
+    /// The name of the series the post is part of, together with
+    /// `series_part`
+    #[serde(default)]
+    series: Option<String>,
+    /// Parts are numbered from 1
+    #[serde(default)]
+    series_part: Option<usize>,
+    /// Whether to show a table of contents, by default depending on the
+    /// number of headings
+    #[serde(default)]
+    toc: Option<bool>,
+    /// When the post was last revised, not before `date`
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    updated: Option<time::OffsetDateTime>,
+    /// What was revised, between `date` and `updated`
+    #[serde(default)]
+    changes: Vec<Change>,
 }
 
-fn frame(title: &str, inner: Markup, input_path: &Path) -> Markup {
-    let year = time::OffsetDateTime::now_utc().year();
+impl Frontmatter {
+    /// `updated`, otherwise the publication date
+    fn last_modified(&self) -> time::OffsetDateTime {
+        self.updated.unwrap_or(self.timestamp)
+    }
+
+    fn validate_updates(&self) -> Result<(), String> {
+        if let Some(updated) = self.updated {
+            if updated < self.timestamp {
+                return Err(format!(
+                    "updated {updated} is earlier than date {}",
+                    self.timestamp
+                ));
+            }
+        } else if !self.changes.is_empty() {
+            return Err("changes require updated to be set".to_owned());
+        }
 
-    struct Page {
-        name: &'static str,
-        link: String,
+        for change in &self.changes {
+            if change.date < self.timestamp || change.date > self.last_modified() {
+                return Err(format!(
+                    "change {:?} of {} is not between date and updated",
+                    change.description, change.date
+                ));
+            }
+        }
+
+        Ok(())
     }
+}
 
-    let pages = [
-        Page {
-            name: "Blog",
-            link: "/blog/index.html".into(),
-        },
-        Page {
-            name: "Skills",
-            link: "/skills/index.html".into(),
-        },
-        Page {
-            name: "Projects",
-            link: "/projects/index.html".into(),
-        },
-        Page {
-            name: "About Me",
-            link: "/about/index.html".into(),
-        },
-    ];
+fn frame(
+    meta: &meta::Metadata,
+    inner: Markup,
+    config: &config::SiteConfig,
+    icons: &icon::IconRegistry,
+) -> Markup {
+    let year = time::OffsetDateTime::now_utc().year();
 
     let output = html!(
         (maud::DOCTYPE)
-        html {
+        html lang=(config.language) {
             head {
-                title { (title) }
-                link rel="stylesheet" href="/reset.css" {}
-                link rel="stylesheet" href="/style.css" {}
-                link rel="icon" href="/favicon.svg"
-                script src="https://unpkg.com/htmx.org@1.9.9" {}
-                meta charset="utf-8" {}
-                meta name="viewport" content="width=device-width, initial-scale=1.0" {}
+                title { (meta.title) }
+                link rel="stylesheet" href=(css::PLACEHOLDER);
+                link rel="icon" href="/favicon.svg";
+                link rel="alternate" type="application/atom+xml" title=(config.title) href=(format!("/{}", feed::ATOM_PATH));
+                script { (PreEscaped(theme::SCRIPT)) }
+                @for script in config.scripts.values().filter(|script| script.enabled) {
+                    script src=(script.output_path()) integrity=(script.integrity) {}
+                }
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1.0";
+                (meta.markup(config))
             }
-            body hx-boost="true" {
+            body hx-boost=[config.htmx().then_some("true")] {
                 header {
                     nav aria-label="main navigation" {
                         a .title href="/index.html" {
-                            (data::FULLNAME)
+                            (config.title)
                         }
                         div .links {
-                            @for page in &pages {
+                            @for page in &config.navigation {
                                 a
                                     .link
                                     href=(page.link)
@@ -121,40 +191,59 @@ fn frame(title: &str, inner: Markup, input_path: &Path) -> Markup {
                                     (page.name)
                                 }
                             }
+                            button
+                                .link
+                                .theme-toggle
+                                type="button"
+                                onclick="toggleTheme()"
+                                title="Toggle dark mode"
+                                aria-label="Toggle dark mode"
+                            {
+                                span aria-hidden="true" { "◐" }
+                            }
                         }
                     }
                 }
 
                 (inner)
 
+                @if let Some(history) = &meta.history {
+                    (history.markup(config))
+                }
+
                 footer {
                     div .socials {
-                        @for social in &data::socials(input_path) {
+                        @for social in &config.footer.socials {
+                            @let icon = icons.resolve(&social.icon).unwrap_or_else(|e| {
+                                panic!("invalid icon for social {:?} in configuration: {e}", social.name)
+                            });
                             a
                                 href=(social.link)
-                                title=(social.description.unwrap_or(&format!("Me on {}", social.name)))
+                                title=(social.description.clone().unwrap_or(format!("Me on {}", social.name)))
                                 target="_blank" rel="noopener noreferrer"
                             {
-                                img src=(social.icon.output_path()) {}
+                                (icons.markup(&icon, &social.name))
                             }
                         }
                     }
 
-                    div .badges {
-                        @for certification in data::certifications() {
-                            a
-                                href=(certification.link)
-                                title=(certification.title)
-                                target="_blank" rel="noopener noreferrer"
-                            {
-                                figure {
-                                    img src=(certification.image) {}
+                    @if config.footer.badges {
+                        div .badges {
+                            @for certification in data::certifications() {
+                                a
+                                    href=(certification.link)
+                                    title=(certification.title)
+                                    target="_blank" rel="noopener noreferrer"
+                                {
+                                    figure {
+                                        img src=(certification.image) alt=(certification.title);
+                                    }
                                 }
                             }
                         }
                     }
                     div .copyright {
-                        span { (format!("© {}, {year}", data::FULLNAME)) }
+                        span { (format!("© {}, {year}", config.author)) }
                     }
 
                 }
@@ -168,35 +257,76 @@ fn frame(title: &str, inner: Markup, input_path: &Path) -> Markup {
 struct Blogpost {
     frontmatter: Frontmatter,
     html_filename: String,
+    /// The markdown source
+    path: PathBuf,
+    /// Everything after the frontmatter
+    content: String,
+    /// Without code blocks
+    words: usize,
 }
 
-fn render_blogposts(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
-    let (dir, index) = ("blog", "index.html");
+struct Blog {
+    pages: Vec<Page>,
+    /// Newest first
+    posts: Vec<Blogpost>,
+    search_index: search::Index,
+}
+
+/// Posts are rendered in two phases: first, all posts are read, then each one
+/// is rendered, as they link to each other.
+fn render_blogposts(
+    output_base_path: &Path,
+    input_path: &Path,
+    renderer: &render::Renderer,
+    config: &config::SiteConfig,
+    icons: &icon::IconRegistry,
+    history: &git::History,
+    check_mode: &CheckMode,
+) -> Blog {
+    let dir = config.blog.path.as_str();
 
     let mut pages = vec![];
-    let adapter = SyntectAdapter::new("InspiredGitHub");
+    let mut search_index = search::Index::default();
+    let adapter = highlight::adapter(&config.highlighting, input_path);
 
     let out = output_base_path.join(dir);
     std::fs::create_dir_all(out.as_path()).unwrap();
 
+    let md_options = Options {
+        render: {
+            let mut builder = comrak::RenderOptionsBuilder::default();
+            builder.github_pre_lang(true);
+            builder.build().unwrap()
+        },
+        extension: {
+            let mut builder = comrak::ExtensionOptionsBuilder::default();
+            builder.header_ids(None);
+            builder.table(true);
+            builder.footnotes(true);
+            builder.build().unwrap()
+        },
+        ..Default::default()
+    };
+
     let mut blog_posts: Vec<Blogpost> = vec![];
 
     for entry in std::fs::read_dir(input_path.join("blog")).unwrap() {
         let entry = entry.unwrap();
         let path = entry.path();
 
-        if !entry.file_type().unwrap().is_file() {
-            panic!("not a file: {path:?}");
+        // directories and other files may contain images referenced by posts,
+        // these are published when rendering the post that references them
+        if entry.file_type().unwrap().is_dir() {
+            continue;
         }
 
         if path.extension().unwrap() != "md" {
+            if FileType::detect(path.to_str().unwrap()).is_ok() {
+                continue;
+            }
             panic!("unknown file type found: {path:?}");
         }
 
-        let mut plugins = Plugins::default();
-
-        plugins.render.codefence_syntax_highlighter = Some(&adapter);
-
         let file = std::fs::read_to_string(&path).unwrap();
         let mut file = file.lines();
 
@@ -207,26 +337,83 @@ fn render_blogposts(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
             .collect::<String>();
 
         let frontmatter: Frontmatter = toml::from_str(&frontmatter).unwrap();
+        if let Err(e) = frontmatter.validate_updates() {
+            panic!("{path:?}: {e}");
+        }
 
-        let rest = file.map(|l| format!("{l}\n")).collect::<String>();
+        let content = file.map(|l| format!("{l}\n")).collect::<String>();
+
+        let words = search::text(parse_document(&Arena::new(), &content, &md_options))
+            .split_whitespace()
+            .count();
+
+        if let Some(image) = &frontmatter.image {
+            if !image.contains("://")
+                && !input_path
+                    .join("static")
+                    .join(image.trim_start_matches('/'))
+                    .is_file()
+            {
+                check_mode.report(format!(
+                    "{path:?}: preview image {image:?} not found in static/"
+                ));
+            }
+        }
 
-        let md_options = Options {
-            render: {
-                let mut builder = comrak::RenderOptionsBuilder::default();
-                builder.github_pre_lang(true);
-                builder.build().unwrap()
-            },
-            extension: {
-                let mut builder = comrak::ExtensionOptionsBuilder::default();
-                builder.header_ids(None);
-                builder.table(true);
-                builder.footnotes(true);
-                builder.build().unwrap()
-            },
-            ..Default::default()
-        };
+        let mut html_path = path.clone();
+        assert!(html_path.set_extension("html"));
+        let html_filename = html_path.file_name().unwrap().to_str().unwrap().to_owned();
+
+        blog_posts.push(Blogpost {
+            frontmatter,
+            html_filename,
+            path,
+            content,
+            words,
+        });
+    }
 
-        let output = markdown_to_html_with_plugins(&rest, &md_options, &plugins);
+    blog_posts.sort_by_key(|post| post.frontmatter.timestamp);
+    blog_posts.reverse();
+
+    let series = blog::series(&blog_posts, check_mode);
+
+    for (position, blog_post) in blog_posts.iter().enumerate() {
+        let Blogpost {
+            frontmatter,
+            html_filename,
+            path,
+            content,
+            words,
+        } = blog_post;
+
+        let mut plugins = Plugins::default();
+
+        plugins.render.codefence_syntax_highlighter = Some(&adapter);
+
+        let arena = Arena::new();
+        let root = parse_document(&arena, content, &md_options);
+
+        let images =
+            markdown::process_images(root, path, input_path, output_base_path, dir, check_mode);
+        markdown::nest_headings(root);
+
+        let text = search::text(root);
+        let headings = markdown::headings(root, &["blogpost"]);
+        let toc = frontmatter
+            .toc
+            .unwrap_or(headings.0.len() >= config.blog.toc_threshold);
+
+        let mut output = vec![];
+        format_html_with_plugins(root, &md_options, &mut output, &plugins).unwrap();
+        let output =
+            headings.add_anchors(&images.add_attributes(&String::from_utf8(output).unwrap()));
+
+        for page in images.pages {
+            if !pages.iter().any(|p: &Page| p.path == page.path) {
+                pages.push(page);
+            }
+        }
 
         let inner = html!(
             article #blogpost {
@@ -235,74 +422,101 @@ fn render_blogposts(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
                     div .meta {
                         p .summary { (maud::PreEscaped(&frontmatter.summary)) }
                         p .date { (frontmatter.timestamp.date()) }
+                        p .reading-time {
+                            (format!("{words} words, {} min read", blog::reading_time(*words)))
+                        }
+                    }
+                    (blog::updates(frontmatter))
+                    @if toc {
+                        (blog::table_of_contents(&headings))
                     }
                 }
+                (blog::series_navigation(&series, blog_post, config))
                 div .content {
                     (maud::PreEscaped(output))
                 }
+                (blog::post_navigation(&blog_posts, position, config))
+                (blog::related_posts(&blog_posts, position, config))
             }
         );
 
-        let output = frame(&frontmatter.title, inner, input_path).into_string();
+        let page_path = Path::new(dir)
+            .join(html_filename)
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let source = path.strip_prefix(input_path).unwrap().to_str().unwrap();
+        let page_history = history.page(&[git::Source::File(source)]);
+        if let (Some(page_history), Some(tolerance)) = (&page_history, config.git.date_tolerance) {
+            let first_commit = page_history.first_commit().timestamp;
+            let difference = (first_commit - frontmatter.timestamp).abs();
+            if difference > time::Duration::days(tolerance.into()) {
+                println!(
+                    "warning: {path:?}: date {} is {} days away from the first commit of the file on {}",
+                    frontmatter.timestamp.date(),
+                    difference.whole_days(),
+                    first_commit.date()
+                );
+            }
+        }
 
-        let mut path = path.clone();
-        assert!(path.set_extension("html"));
-        let html_filename = path.file_name().unwrap().to_str().unwrap();
+        let tags: Vec<&str> = frontmatter.tags.iter().map(|tag| tag.0.as_str()).collect();
+        let meta = meta::Metadata {
+            title: &frontmatter.title,
+            path: &page_path,
+            description: meta::plain_text(&frontmatter.summary),
+            image: frontmatter.image.as_deref(),
+            article: Some(meta::Article {
+                published: frontmatter.timestamp,
+                modified: frontmatter.updated,
+                tags: tags.clone(),
+                words: *words,
+            }),
+            structured_data: vec![],
+            history: page_history,
+        };
+        let output = frame(&meta, inner, config, icons);
+
+        search_index.add(
+            search::Document {
+                title: frontmatter.title.clone(),
+                url: config.blog.link(html_filename),
+                summary: meta.description.clone(),
+                date: frontmatter.timestamp.date().to_string(),
+            },
+            &tags,
+            &text,
+        );
 
-        let out_path = out.as_path().join(html_filename);
-        let mut handle = std::fs::File::create(&out_path).unwrap();
-        handle.write_all(output.as_bytes()).unwrap();
+        renderer.render_into(output, &out.as_path().join(html_filename));
 
         pages.push(Page {
-            path: Path::new(dir)
-                .join(html_filename)
-                .to_str()
-                .unwrap()
-                .to_owned(),
+            path: page_path,
             filetype: FileType::Html,
         });
-
-        blog_posts.push(Blogpost {
-            frontmatter,
-            html_filename: html_filename.to_string(),
-        });
     }
 
-    blog_posts.sort_by_key(|post| post.frontmatter.timestamp);
-    blog_posts.reverse();
-
-    let inner = html!(
-        div .postlist {
-            table {
-                tbody {
-                    @for blog_post in &blog_posts {
-                        tr {
-                            td {
-                                a href=(Path::new("/blog").join(blog_post.html_filename.clone()).to_str().unwrap()) {
-                                    (blog_post.frontmatter.title)
-                                }
-                            }
-                            td {
-                                (blog_post.frontmatter.timestamp.date())
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    );
-
-    let output = frame("Blog posts", inner, input_path);
-
-    let output_path = &out.as_path().join(index);
-    render::render_into(output, output_path);
-
-    pages.push(Page {
-        path: Path::new(dir).join(index).to_str().unwrap().to_owned(),
-        filetype: FileType::Html,
-    });
+    pages.append(&mut blog::render_index(
+        output_base_path,
+        &blog_posts,
+        renderer,
+        config,
+        icons,
+    ));
+    pages.append(&mut blog::render_series(
+        output_base_path,
+        &series,
+        renderer,
+        config,
+        icons,
+    ));
 
-    pages
+    Blog {
+        pages,
+        posts: blog_posts,
+        search_index,
+    }
 }
 
 #[derive(PartialEq, Eq)]
@@ -311,6 +525,16 @@ enum CheckMode {
     Strict,
 }
 
+impl CheckMode {
+    /// Fails the build in strict mode, otherwise prints a warning.
+    fn report(&self, message: impl std::fmt::Display) {
+        match self {
+            Self::Strict => panic!("{message}"),
+            Self::Relaxed => println!("warning: {message}"),
+        }
+    }
+}
+
 impl TryFrom<String> for CheckMode {
     type Error = String;
 
@@ -323,18 +547,28 @@ impl TryFrom<String> for CheckMode {
     }
 }
 
+enum Command {
+    Build(CheckMode, PathBuf),
+    CheckLinks(audit::Mode, PathBuf),
+    Vendor,
+}
+
 fn main() {
-    let mut pages: Vec<Page> = vec![];
-    let (check_mode, output_base_path): (CheckMode, PathBuf) = {
+    let command = {
         let mut args = env::args().skip(1);
-        (
-            args.next().unwrap().try_into().unwrap(),
-            PathBuf::from(args.next().unwrap()),
-        )
+        match args.next().unwrap().as_str() {
+            "vendor" => Command::Vendor,
+            "check-links" => Command::CheckLinks(
+                args.next().unwrap().try_into().unwrap(),
+                PathBuf::from(args.next().unwrap()),
+            ),
+            check_mode => Command::Build(
+                check_mode.to_owned().try_into().unwrap(),
+                PathBuf::from(args.next().unwrap()),
+            ),
+        }
     };
 
-    assert!(output_base_path.is_absolute());
-
     let input_path = Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("..")
         .canonicalize()
@@ -342,43 +576,137 @@ fn main() {
 
     assert!(input_path.exists());
 
+    match command {
+        Command::Build(check_mode, output_base_path) => {
+            build(&check_mode, &output_base_path, &input_path);
+        }
+        Command::CheckLinks(mode, output_base_path) => {
+            let external_links = build(&CheckMode::Relaxed, &output_base_path, &input_path);
+            audit::run(&mode, &external_links, &input_path);
+        }
+        Command::Vendor => {
+            let config = config::SiteConfig::load(&input_path);
+            vendor::fetch(&config.scripts, &input_path);
+        }
+    }
+}
+
+fn build(
+    check_mode: &CheckMode,
+    output_base_path: &Path,
+    input_path: &Path,
+) -> BTreeMap<String, BTreeSet<String>> {
+    assert!(output_base_path.is_absolute());
+
+    let mut pages: Vec<Page> = vec![];
+
     let build_directory = "build";
 
     let rendered_output_directory = output_base_path.join(build_directory);
 
-    std::fs::create_dir_all(output_base_path.as_path()).unwrap();
+    std::fs::create_dir_all(output_base_path).unwrap();
     std::fs::create_dir_all(rendered_output_directory.as_path()).unwrap();
 
-    pages.append(&mut render_blogposts(
+    let mut config = config::SiteConfig::load(input_path);
+    vendor::check_vendored(&mut config.scripts, input_path, check_mode);
+
+    let icons = icon::IconRegistry::new(input_path, config.icons.mode);
+    let renderer = render::Renderer::new(config.html.minify);
+
+    let history = git::History::load(input_path);
+
+    let mut blog = render_blogposts(
         &rendered_output_directory,
-        &input_path,
-    ));
+        input_path,
+        &renderer,
+        &config,
+        &icons,
+        &history,
+        check_mode,
+    );
+    pages.append(&mut blog.pages);
+    pages.push(feed::atom(&rendered_output_directory, &blog.posts, &config));
+    if config.search.mode == config::SearchMode::Client {
+        pages.push(blog.search_index.write(&rendered_output_directory));
+    }
+    let search_documents = blog.search_index.write_documents(output_base_path);
+
+    /// Renders a page and records when it was last changed, for the sitemap
+    fn render<P: pages::Render>(
+        rendered_output_directory: &Path,
+        renderer: &render::Renderer,
+        config: &config::SiteConfig,
+        icons: &icon::IconRegistry,
+        history: &git::History,
+        modified: &mut BTreeMap<String, time::OffsetDateTime>,
+    ) -> Vec<Page> {
+        let pages = P::render(rendered_output_directory, renderer, config, icons, history);
+        if let Some(timestamp) = history.last_modified(P::SOURCES) {
+            modified.extend(pages.iter().map(|page| (page.path.clone(), timestamp)));
+        }
+        pages
+    }
 
-    pages.append(&mut pages::LandingPage::render(
+    let mut modified: BTreeMap<String, time::OffsetDateTime> = blog
+        .posts
+        .iter()
+        .filter_map(|blog_post| {
+            let source = blog_post.path.strip_prefix(input_path).unwrap();
+            let timestamp =
+                history.last_modified(&[git::Source::File(source.to_str().unwrap())])?;
+            Some((
+                format!("{}/{}", config.blog.path, blog_post.html_filename),
+                timestamp,
+            ))
+        })
+        .collect();
+
+    pages.append(&mut render::<pages::LandingPage>(
         &rendered_output_directory,
-        &input_path,
+        &renderer,
+        &config,
+        &icons,
+        &history,
+        &mut modified,
     ));
-    pages.append(&mut pages::SkillsPage::render(
+    pages.append(&mut render::<pages::SkillsPage>(
         &rendered_output_directory,
-        &input_path,
+        &renderer,
+        &config,
+        &icons,
+        &history,
+        &mut modified,
     ));
-    pages.append(&mut pages::ProjectsPage::render(
+    pages.append(&mut render::<pages::ProjectsPage>(
         &rendered_output_directory,
-        &input_path,
+        &renderer,
+        &config,
+        &icons,
+        &history,
+        &mut modified,
     ));
-    pages.append(&mut pages::AboutPage::render(
+    pages.append(&mut render::<pages::AboutPage>(
         &rendered_output_directory,
-        &input_path,
+        &renderer,
+        &config,
+        &icons,
+        &history,
+        &mut modified,
+    ));
+    pages.append(&mut render::<pages::SearchPage>(
+        &rendered_output_directory,
+        &renderer,
+        &config,
+        &icons,
+        &history,
+        &mut modified,
     ));
 
-    let icons = icon::IconsUnverified::verify_all(
-        if check_mode == CheckMode::Relaxed {
-            icon::UnusedIconFiles::Allow
-        } else {
-            icon::UnusedIconFiles::Deny
-        },
-        &input_path,
-    );
+    let icons = icons.verify_all(if *check_mode == CheckMode::Relaxed {
+        icon::UnusedIconFiles::Allow
+    } else {
+        icon::UnusedIconFiles::Deny
+    });
 
     fn copy(rendered_output_directory: &Path, path: &'static str, input_path: &Path) -> Page {
         std::fs::copy(
@@ -388,19 +716,40 @@ fn main() {
         .unwrap();
         Page {
             path: path.to_owned(),
-            filetype: FileType::Css,
+            filetype: FileType::detect(path).unwrap(),
         }
     }
 
-    pages.push(copy(&rendered_output_directory, "reset.css", &input_path));
-    pages.push(copy(&rendered_output_directory, "style.css", &input_path));
-    pages.push(copy(&rendered_output_directory, "favicon.svg", &input_path));
+    pages.push(css::bundle(
+        &config.css,
+        &[
+            theme::stylesheet(&config.theme),
+            highlight::stylesheet(&config.highlighting),
+        ],
+        input_path,
+        &rendered_output_directory,
+        &pages,
+    ));
+    pages.append(&mut vendor::copy_all(
+        &config.scripts,
+        input_path,
+        &rendered_output_directory,
+    ));
+    pages.push(copy(&rendered_output_directory, "favicon.svg", input_path));
+    pages.push(feed::sitemap(
+        &rendered_output_directory,
+        &pages,
+        &blog.posts,
+        &modified,
+        &config,
+    ));
 
     pages.append(
-        &mut fs::copy_dir_all(
+        &mut fs::copy_dir_all_with(
             input_path.join("static/assets"),
             &rendered_output_directory,
             Path::new("assets"),
+            &|path, content| Ok(image::process_asset(path, content, check_mode)),
         )
         .unwrap()
         .into_iter()
@@ -410,15 +759,30 @@ fn main() {
         })
         .collect(),
     );
-    pages.append(&mut icons.copy_all(&rendered_output_directory, &input_path));
+    pages.append(&mut icons.copy_all(&rendered_output_directory, input_path));
+
+    let external_links = links::check(
+        &rendered_output_directory,
+        &pages,
+        &config.base_url,
+        check_mode,
+    );
+    lint::check(&rendered_output_directory, &pages, check_mode);
 
     let manifest = Manifest {
         pages,
         content_directory: Path::new(build_directory).to_owned(),
+        search_documents,
     };
 
     let mut handle = std::fs::File::create(output_base_path.join("manifest.json")).unwrap();
     handle
         .write_all(serde_json::to_string(&manifest).unwrap().as_bytes())
         .unwrap();
+
+    if let Some(summary) = renderer.summary() {
+        println!("{summary}");
+    }
+
+    external_links
 }
@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+use std::path::{Component, Path};
+
+use comrak::nodes::{AstNode, NodeValue};
+
+use super::image;
+use super::{CheckMode, FileType, Page};
+
+fn is_external(url: &str) -> bool {
+    url.contains("://") || url.starts_with("//") || url.starts_with("data:")
+}
+
+struct ResolvedImage {
+    local: std::path::PathBuf,
+    /// The URL to use in the rendered post
+    url: String,
+    /// The output path, for images that have to be published together with the
+    /// post. Images from `static/` are already handled by the asset pipeline.
+    publish: Option<String>,
+}
+
+/// Resolves an image reference from a post. Absolute paths are looked up in
+/// `static/`, relative paths next to the post first, then in `static/`.
+fn resolve_image(
+    url: &str,
+    post_path: &Path,
+    input_path: &Path,
+    output_directory: &str,
+) -> Result<ResolvedImage, String> {
+    let relative = Path::new(url.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "image path {url:?} must not contain relative components"
+        ));
+    }
+
+    let static_path = input_path.join("static").join(relative);
+    let static_image = || ResolvedImage {
+        local: static_path.clone(),
+        url: Path::new("/").join(relative).to_str().unwrap().to_owned(),
+        publish: None,
+    };
+
+    if url.starts_with('/') {
+        return if static_path.is_file() {
+            Ok(static_image())
+        } else {
+            Err(format!("image {url:?} not found at {static_path:?}"))
+        };
+    }
+
+    let post_local = post_path.parent().unwrap().join(relative);
+    if post_local.is_file() {
+        Ok(ResolvedImage {
+            local: post_local,
+            url: url.to_owned(),
+            publish: Some(
+                Path::new(output_directory)
+                    .join(relative)
+                    .to_str()
+                    .unwrap()
+                    .to_owned(),
+            ),
+        })
+    } else if static_path.is_file() {
+        Ok(static_image())
+    } else {
+        Err(format!(
+            "image {url:?} found neither at {post_local:?} nor at {static_path:?}"
+        ))
+    }
+}
+
+pub struct Images {
+    /// One entry per image in document order, which is also the order comrak
+    /// renders them in.
+    dimensions: Vec<Option<(u32, u32)>>,
+    pub pages: Vec<Page>,
+}
+
+impl Images {
+    /// Adds lazy loading and, if known, the dimensions to all `img` tags of a
+    /// rendered post. Raw HTML is not rendered by comrak, so all `img` tags in
+    /// the output come from markdown images.
+    pub fn add_attributes(&self, html: &str) -> String {
+        let mut output = String::with_capacity(html.len());
+        let mut parts = html.split("<img ");
+
+        output.push_str(parts.next().unwrap());
+        for (i, part) in parts.enumerate() {
+            output.push_str("<img loading=\"lazy\" ");
+            if let Some(Some((width, height))) = self.dimensions.get(i) {
+                output.push_str(&format!("width=\"{width}\" height=\"{height}\" "));
+            }
+            output.push_str(part);
+        }
+        output
+    }
+}
+
+/// Validates all local images of a post, rewrites their URLs so they work from
+/// the rendered post and publishes the ones that live next to the post into
+/// `output_directory`, the directory the post is rendered to.
+pub fn process_images<'a>(
+    root: &'a AstNode<'a>,
+    post_path: &Path,
+    input_path: &Path,
+    output_base_path: &Path,
+    output_directory: &str,
+    check_mode: &CheckMode,
+) -> Images {
+    let mut images = Images {
+        dimensions: vec![],
+        pages: vec![],
+    };
+
+    for node in root.descendants() {
+        let mut data = node.data.borrow_mut();
+        let NodeValue::Image(ref mut link) = data.value else {
+            continue;
+        };
+
+        if is_external(&link.url) {
+            images.dimensions.push(None);
+            continue;
+        }
+
+        let resolved = match resolve_image(&link.url, post_path, input_path, output_directory) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                check_mode.report(format!("{post_path:?}: {e}"));
+                images.dimensions.push(None);
+                continue;
+            }
+        };
+
+        let content = std::fs::read(&resolved.local).unwrap();
+        images.dimensions.push(image::dimensions(&content));
+
+        link.url = resolved.url;
+
+        let Some(output) = resolved.publish else {
+            continue;
+        };
+        let filetype = match FileType::detect(&output) {
+            Ok(filetype) => filetype,
+            Err(e) => {
+                check_mode.report(format!("{post_path:?}: image {output:?}: {e}"));
+                continue;
+            }
+        };
+        let Some(content) = image::process_asset(&resolved.local, content, check_mode) else {
+            continue;
+        };
+
+        let output_path = output_base_path.join(&output);
+        std::fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+        std::fs::write(&output_path, content).unwrap();
+
+        images.pages.push(Page {
+            filetype,
+            path: output,
+        });
+    }
+
+    images
+}
+
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub id: String,
+}
+
+/// The headings of a post in document order, which is also the order comrak
+/// renders them in.
+pub struct Headings(pub Vec<Heading>);
+
+pub(crate) fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+/// Shifts the levels of all headings so that the top ones become h2, below
+/// the title of the page.
+pub fn nest_headings<'a>(root: &'a AstNode<'a>) {
+    let top = root
+        .descendants()
+        .filter_map(|node| match node.data.borrow().value {
+            NodeValue::Heading(heading) => Some(heading.level),
+            _ => None,
+        })
+        .min();
+    let Some(top) = top else {
+        return;
+    };
+
+    for node in root.descendants() {
+        if let NodeValue::Heading(heading) = &mut node.data.borrow_mut().value {
+            heading.level = (heading.level + 2 - top).min(6);
+        }
+    }
+}
+
+/// Collects all headings and assigns each a unique id derived from its text.
+/// `reserved` are ids that are already used by the page around the post.
+pub fn headings<'a>(root: &'a AstNode<'a>, reserved: &[&str]) -> Headings {
+    let mut used: HashSet<String> = reserved.iter().map(|id| id.to_string()).collect();
+    let mut headings = vec![];
+
+    for node in root.descendants() {
+        let NodeValue::Heading(heading) = node.data.borrow().value else {
+            continue;
+        };
+
+        let mut text = String::new();
+        for child in node.descendants() {
+            match &child.data.borrow().value {
+                NodeValue::Text(content) => text.push_str(content),
+                NodeValue::Code(code) => text.push_str(&code.literal),
+                NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+                _ => (),
+            }
+        }
+
+        let base = match slug(&text) {
+            slug if slug.is_empty() => "section".to_owned(),
+            slug => slug,
+        };
+        let id = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base}-{n}"),
+            })
+            .find(|id| !used.contains(id))
+            .unwrap();
+        used.insert(id.clone());
+
+        headings.push(Heading {
+            level: heading.level,
+            text,
+            id,
+        });
+    }
+
+    Headings(headings)
+}
+
+impl Headings {
+    /// Adds the ids and a permalink to all headings of a rendered post. Like
+    /// for images, all headings in the output come from markdown.
+    pub fn add_anchors(&self, html: &str) -> String {
+        let mut output = String::with_capacity(html.len());
+        let mut rest = html;
+
+        for heading in &self.0 {
+            let start = format!("<h{}>", heading.level);
+            let end = format!("</h{}>", heading.level);
+
+            let Some(position) = rest.find(&start) else {
+                break;
+            };
+            output.push_str(&rest[..position]);
+            output.push_str(&format!("<h{} id=\"{}\">", heading.level, heading.id));
+            rest = &rest[position + start.len()..];
+
+            let position = rest.find(&end).unwrap();
+            output.push_str(&rest[..position]);
+            output.push_str(&format!(
+                " <a class=\"permalink\" href=\"#{}\" aria-label=\"Link to this section\">¶</a>",
+                heading.id
+            ));
+            output.push_str(&end);
+            rest = &rest[position + end.len()..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+}
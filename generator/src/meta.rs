@@ -0,0 +1,117 @@
+use maud::{html, Markup};
+use time::format_description::well_known::Rfc3339;
+
+use super::config::SiteConfig;
+use super::git::PageHistory;
+use super::html::{self, Token};
+use super::jsonld;
+
+pub struct Article<'a> {
+    pub published: time::OffsetDateTime,
+    pub modified: Option<time::OffsetDateTime>,
+    pub tags: Vec<&'a str>,
+    pub words: usize,
+}
+
+/// Everything `frame` needs to describe a page to search engines and link
+/// previews.
+pub struct Metadata<'a> {
+    pub title: &'a str,
+    /// The output path of the page, relative to the root of the site
+    pub path: &'a str,
+    pub description: String,
+    /// Falls back to the image of the site
+    pub image: Option<&'a str>,
+    pub article: Option<Article<'a>>,
+    /// JSON-LD objects in addition to the `BlogPosting` of articles
+    pub structured_data: Vec<serde_json::Value>,
+    /// The commits of the files the page is made from
+    pub history: Option<PageHistory<'a>>,
+}
+
+impl<'a> Metadata<'a> {
+    pub fn page(title: &'a str, path: &'a str, config: &SiteConfig) -> Self {
+        Self {
+            title,
+            path,
+            description: config.description.clone(),
+            image: None,
+            article: None,
+            structured_data: vec![],
+            history: None,
+        }
+    }
+
+    /// An `updated` date of an article, otherwise the last commit.
+    pub fn modified(&self) -> Option<time::OffsetDateTime> {
+        self.article
+            .as_ref()
+            .and_then(|article| article.modified)
+            .or(self.history.as_ref().map(PageHistory::last_modified))
+    }
+
+    pub fn markup(&self, config: &SiteConfig) -> Markup {
+        let url = config.url(self.path);
+        let image = config.url(self.image.unwrap_or(&config.image));
+        let card = if self.image.is_some() {
+            "summary_large_image"
+        } else {
+            "summary"
+        };
+
+        html!(
+            meta name="description" content=(self.description);
+            link rel="canonical" href=(url);
+
+            meta property="og:title" content=(self.title);
+            meta property="og:description" content=(self.description);
+            meta property="og:url" content=(url);
+            meta property="og:image" content=(image);
+            meta property="og:site_name" content=(config.title);
+            meta property="og:locale" content=(config.language.replace('-', "_"));
+            @if let Some(history) = &self.history {
+                meta property="og:updated_time" content=(history.last_modified().format(&Rfc3339).unwrap());
+            }
+
+            @if let Some(article) = &self.article {
+                meta property="og:type" content="article";
+                meta property="article:published_time" content=(article.published.format(&Rfc3339).unwrap());
+                @if let Some(modified) = self.modified() {
+                    meta property="article:modified_time" content=(modified.format(&Rfc3339).unwrap());
+                }
+                meta property="article:author" content=(config.author);
+                @for tag in &article.tags {
+                    meta property="article:tag" content=(tag);
+                }
+            } @else {
+                meta property="og:type" content="website";
+            }
+
+            meta name="twitter:card" content=(card);
+            meta name="twitter:title" content=(self.title);
+            meta name="twitter:description" content=(self.description);
+            meta name="twitter:image" content=(image);
+
+            @if let Some(posting) = jsonld::blog_posting(self, config) {
+                (jsonld::markup(posting))
+            }
+            @for value in &self.structured_data {
+                (jsonld::markup(value.clone()))
+            }
+        )
+    }
+}
+
+/// The text content of an HTML fragment, with whitespace collapsed.
+pub fn plain_text(fragment: &str) -> String {
+    html::tokenize(fragment)
+        .iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(html::decode_entities(text)),
+            _ => None,
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
@@ -0,0 +1,167 @@
+use super::html::{self, Tag, Token};
+
+/// Elements whose content is written out unchanged.
+const PRESERVED_ELEMENTS: &[&str] = &["pre", "code", "textarea", "script", "style"];
+
+/// Elements that are rendered inline, so whitespace next to them is
+/// significant.
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "button", "cite", "code", "data", "dfn", "em", "i",
+    "img", "input", "kbd", "label", "mark", "q", "s", "samp", "select", "small", "span", "strong",
+    "sub", "sup", "svg", "textarea", "time", "u", "var", "wbr",
+];
+
+fn is_inline(token: Option<&Token>) -> bool {
+    match token {
+        Some(Token::StartTag(tag)) => INLINE_ELEMENTS.contains(&tag.name.as_str()),
+        Some(Token::EndTag(name)) => INLINE_ELEMENTS.contains(&name.as_str()),
+        Some(Token::Text(_)) => true,
+        _ => false,
+    }
+}
+
+fn starts_any(token: Option<&Token>, names: &[&str]) -> bool {
+    matches!(token, Some(Token::StartTag(tag)) if names.contains(&tag.name.as_str()))
+}
+
+/// Whether the end tag can be left out, see "Optional tags" in the HTML
+/// standard. `next` is the token that follows after minification.
+fn end_tag_optional(name: &str, next: Option<&Token>) -> bool {
+    let parent_ends = matches!(next, Some(Token::EndTag(_)) | None);
+    match name {
+        // comments, which would prevent this, are removed beforehand
+        "html" | "body" => true,
+        "head" => matches!(next, Some(Token::StartTag(_))),
+        "li" => parent_ends || starts_any(next, &["li"]),
+        "dt" => starts_any(next, &["dt", "dd"]),
+        "dd" => parent_ends || starts_any(next, &["dt", "dd"]),
+        "td" | "th" => parent_ends || starts_any(next, &["td", "th"]),
+        "tr" => parent_ends || starts_any(next, &["tr"]),
+        "thead" => starts_any(next, &["tbody", "tfoot"]),
+        "tbody" => parent_ends || starts_any(next, &["tbody", "tfoot"]),
+        "tfoot" => parent_ends,
+        "option" => parent_ends || starts_any(next, &["option", "optgroup"]),
+        _ => false,
+    }
+}
+
+fn start_tag_optional(tag: &Tag, next: Option<&Token>) -> bool {
+    tag.attributes.is_empty()
+        && match tag.name.as_str() {
+            "html" => true,
+            "head" => matches!(next, Some(Token::StartTag(_)) | Some(Token::EndTag(_))),
+            _ => false,
+        }
+}
+
+fn can_be_unquoted(value: &str) -> bool {
+    !value.is_empty()
+        && !value.ends_with('/')
+        && !value
+            .chars()
+            .any(|c| c.is_ascii_whitespace() || "\"'=<>`".contains(c))
+}
+
+fn serialize(tag: &Tag) -> String {
+    let mut output = format!("<{}", tag.name);
+    let mut last_unquoted = false;
+    for attribute in &tag.attributes {
+        output.push(' ');
+        output.push_str(&attribute.name);
+        last_unquoted = false;
+        if let Some(value) = &attribute.value {
+            if can_be_unquoted(value) {
+                output.push_str(&format!("={}", value.replace('&', "&amp;")));
+                last_unquoted = true;
+            } else {
+                output.push_str(&format!("=\"{}\"", html::escape_attribute(value)));
+            }
+        }
+    }
+    if tag.self_closing {
+        // an unquoted value would swallow the slash
+        output.push_str(if last_unquoted { " />" } else { "/>" });
+    } else {
+        output.push('>');
+    }
+    output
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !output.ends_with(' ') {
+                output.push(' ');
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// Minifies generated HTML: comments are removed, whitespace is collapsed,
+/// attribute quotes and optional tags are dropped. The content of `pre` and
+/// `code` blocks is left alone.
+pub fn html(source: &str) -> String {
+    let mut tokens = html::tokenize(source);
+
+    // removing comments first makes the decisions about optional tags simpler
+    tokens.retain(|token| !matches!(token, Token::Comment));
+
+    let mut output = String::with_capacity(source.len());
+    let mut preserved: usize = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        let previous = i.checked_sub(1).and_then(|i| tokens.get(i));
+        let next = tokens.get(i + 1);
+
+        match token {
+            Token::Declaration(declaration) => output.push_str(declaration),
+            Token::Comment => (),
+            Token::StartTag(tag) => {
+                if PRESERVED_ELEMENTS.contains(&tag.name.as_str()) && !tag.self_closing {
+                    preserved += 1;
+                }
+                if preserved == 0 && start_tag_optional(tag, next) {
+                    continue;
+                }
+                output.push_str(&serialize(tag));
+            }
+            Token::EndTag(name) => {
+                if PRESERVED_ELEMENTS.contains(&name.as_str()) {
+                    preserved = preserved.saturating_sub(1);
+                }
+                if preserved == 0 {
+                    // whitespace that would follow is dropped as well
+                    let next = match next {
+                        Some(Token::Text(text)) if text.trim().is_empty() => tokens.get(i + 2),
+                        next => next,
+                    };
+                    if end_tag_optional(name, next) {
+                        continue;
+                    }
+                }
+                output.push_str(&format!("</{name}>"));
+            }
+            Token::Text(text) => {
+                if preserved > 0 {
+                    output.push_str(text);
+                    continue;
+                }
+
+                let mut text = collapse_whitespace(text);
+                if !is_inline(previous) {
+                    text = text.trim_start().to_owned();
+                }
+                if !is_inline(next) {
+                    text = text.trim_end().to_owned();
+                }
+                output.push_str(&text);
+            }
+        }
+    }
+
+    output
+}
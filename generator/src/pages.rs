@@ -1,40 +1,77 @@
 use std::path::Path;
 
+use super::config::{SearchMode, SiteConfig};
 use super::data;
+use super::frame;
+use super::git::{History, Source};
 use super::icon;
-use super::icon::Icon;
-use super::{frame, render};
+use super::icon::IconRegistry;
+use super::jsonld;
+use super::meta::Metadata;
+use super::render::Renderer;
+use super::search;
 use super::{FileType, Page};
 
 use maud::{html, PreEscaped};
 
 pub trait Render {
-    fn render(output_base_path: &Path, input_path: &Path) -> Vec<Page>;
+    /// What the page is made from: its own `impl` block in this file and the
+    /// data it shows. The history of the first source is linked.
+    const SOURCES: &'static [Source<'static>];
+
+    fn render(
+        output_base_path: &Path,
+        renderer: &Renderer,
+        config: &SiteConfig,
+        icons: &IconRegistry,
+        history: &History,
+    ) -> Vec<Page>;
 }
 
 pub struct LandingPage;
 
 impl Render for LandingPage {
-    fn render(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
+    const SOURCES: &'static [Source<'static>] = &[Source::Lines {
+        file: "generator/src/pages.rs",
+        range: "^/^impl Render for LandingPage/,/^}/",
+    }];
+
+    fn render(
+        output_base_path: &Path,
+        renderer: &Renderer,
+        config: &SiteConfig,
+        icons: &IconRegistry,
+        history: &History,
+    ) -> Vec<Page> {
         let (dir, name) = ("", "index.html");
 
         let path = output_base_path.join(dir).join(name);
         let page = html!(
             div #landing {
                 div id="introduction" {
-                    h1 { "Hi!"}
-                    p {
-                        "Hello, welcome to my homepage! Here, you will find some articles
-                        (mostly tech), some info about myself and whatever else I am thinking of."
-                    }
+                    h1 { (config.landing.heading) }
+                    p { (config.landing.introduction) }
                 }
-                img src="assets/profile.jpg" {}
+                img src=(config.landing.image) alt=(config.author);
             }
         );
 
-        render::render_into(frame(data::FULLNAME, page, input_path), &path);
+        let page_path = Path::new(dir).join(name).to_str().unwrap().to_owned();
+        renderer.render_into(
+            frame(
+                &Metadata {
+                    structured_data: vec![jsonld::website(config), jsonld::person(config)],
+                    history: history.page(Self::SOURCES),
+                    ..Metadata::page(&config.title, &page_path, config)
+                },
+                page,
+                config,
+                icons,
+            ),
+            &path,
+        );
         vec![Page {
-            path: Path::new(dir).join(name).to_str().unwrap().to_owned(),
+            path: page_path,
             filetype: FileType::Html,
         }]
     }
@@ -43,7 +80,28 @@ impl Render for LandingPage {
 pub struct SkillsPage;
 
 impl Render for SkillsPage {
-    fn render(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
+    const SOURCES: &'static [Source<'static>] = &[
+        Source::Lines {
+            file: "generator/src/pages.rs",
+            range: "^/^impl Render for SkillsPage/,/^}/",
+        },
+        Source::Lines {
+            file: "generator/src/data.rs",
+            range: "^/^pub fn tech_categories/,/^}/",
+        },
+        Source::Lines {
+            file: "generator/src/data.rs",
+            range: "^/^pub fn certifications/,/^}/",
+        },
+    ];
+
+    fn render(
+        output_base_path: &Path,
+        renderer: &Renderer,
+        config: &SiteConfig,
+        icons: &IconRegistry,
+        history: &History,
+    ) -> Vec<Page> {
         let (dir, name) = ("skills", "index.html");
 
         let directory = output_base_path.join(dir);
@@ -53,14 +111,15 @@ impl Render for SkillsPage {
 
         let page = html!(
             main #skills {
+                h1 .visually-hidden { "Skills" }
                 article #focus-areas {
-                    h1 { "Focus Areas" }
+                    h2 { "Focus Areas" }
                     hr;
                     div {
                         div .column {
-                            section .area style="background-color:#effaf3;" {
-                                h1 {
-                                    img src=(icon!("CloudDownload", input_path).output_path()) {}
+                            section .area .tint-green {
+                                h3 {
+                                    (icons.markup(&*icon!("CloudDownload", icons), ""))
                                     span { "Cloud & Migrations" }
                                 }
 
@@ -89,9 +148,9 @@ impl Render for SkillsPage {
                                 }
                             }
 
-                            section .area style="background-color:#feecf0;" {
-                                h1 {
-                                    img src=(icon!("MagnifyingGlass", input_path).output_path()) {}
+                            section .area .tint-red {
+                                h3 {
+                                    (icons.markup(&*icon!("MagnifyingGlass", icons), ""))
                                     span { "Monitoring & Alerting" }
                                 }
                                 p .slogan {
@@ -116,9 +175,9 @@ impl Render for SkillsPage {
                         }
 
                         div .column {
-                            section .area style="background-color:#eef6fc;" {
-                                h1 {
-                                    img src=(icon!("Network", input_path).output_path()) {}
+                            section .area .tint-blue {
+                                h3 {
+                                    (icons.markup(&*icon!("Network", icons), ""))
                                     span { "DevOps Architecture" }
                                 }
                                 p .slogan {
@@ -160,9 +219,9 @@ impl Render for SkillsPage {
                         }
 
                         div .column {
-                            section .area style="background-color:#fffbeb;" {
-                                h1 {
-                                    img src=(icon!("Shield", input_path).output_path()) {}
+                            section .area .tint-yellow {
+                                h3 {
+                                    (icons.markup(&*icon!("Shield", icons), ""))
                                     span { "Security" }
                                 }
                                 p .slogan {
@@ -188,9 +247,9 @@ impl Render for SkillsPage {
                                     }
                                 }
                             }
-                            section .area style="background-color:#effaf3;" {
-                                h1 {
-                                    img src=(icon!("Gears", input_path).output_path()) {}
+                            section .area .tint-green {
+                                h3 {
+                                    (icons.markup(&*icon!("Gears", icons), ""))
                                     span { "Automation" }
                                 }
                                 p .slogan {
@@ -220,19 +279,19 @@ impl Render for SkillsPage {
                 }
 
                 article #technologies {
-                    h1 { "Technologies" }
+                    h2 { "Technologies" }
                     hr;
                     div {
-                        @for category in data::tech_categories(input_path) {
+                        @for category in data::tech_categories(icons) {
                             section .block {
                                 div .name {
-                                    h1 { (category.name) }
+                                    h3 { (category.name) }
                                 }
                                 div .techlist {
                                     div {
                                         @for tech in category.technologies {
                                             div .tech data-tech-level={(tech.level)} {
-                                                img src=(tech.icon.output_path()) {}
+                                                (icons.markup(&*tech.icon, ""))
                                                 span { (tech.name) }
                                             }
                                         }
@@ -244,14 +303,14 @@ impl Render for SkillsPage {
                 }
 
                 article #certifications {
-                    h1 { "Certifications" }
+                    h2 { "Certifications" }
                     hr;
                     ul {
                         @for certification in data::certifications() {
                             li {
                                 a href=(certification.link) title=(certification.title) target="_blank" rel="noopener noreferrer" {
-                                    img src=(certification.image) {}
-                                    h1 { (certification.title) }
+                                    img src=(certification.image) alt="";
+                                    h3 { (certification.title) }
                                 }
                             }
                         }
@@ -260,9 +319,21 @@ impl Render for SkillsPage {
             }
         );
 
-        render::render_into(frame(data::FULLNAME, page, input_path), &path);
+        let page_path = Path::new(dir).join(name).to_str().unwrap().to_owned();
+        renderer.render_into(
+            frame(
+                &Metadata {
+                    history: history.page(Self::SOURCES),
+                    ..Metadata::page(&config.title, &page_path, config)
+                },
+                page,
+                config,
+                icons,
+            ),
+            &path,
+        );
         vec![Page {
-            path: Path::new(dir).join(name).to_str().unwrap().to_owned(),
+            path: page_path,
             filetype: FileType::Html,
         }]
     }
@@ -271,7 +342,28 @@ impl Render for SkillsPage {
 pub struct ProjectsPage;
 
 impl Render for ProjectsPage {
-    fn render(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
+    const SOURCES: &'static [Source<'static>] = &[
+        Source::Lines {
+            file: "generator/src/pages.rs",
+            range: "^/^impl Render for ProjectsPage/,/^}/",
+        },
+        Source::Lines {
+            file: "generator/src/data.rs",
+            range: "^/^pub fn projects/,/^}/",
+        },
+        Source::Lines {
+            file: "generator/src/data.rs",
+            range: "^/^pub fn contribution_projects/,/^}/",
+        },
+    ];
+
+    fn render(
+        output_base_path: &Path,
+        renderer: &Renderer,
+        config: &SiteConfig,
+        icons: &IconRegistry,
+        history: &History,
+    ) -> Vec<Page> {
         let (dir, name) = ("projects", "index.html");
 
         let directory = output_base_path.join(dir);
@@ -279,22 +371,29 @@ impl Render for ProjectsPage {
 
         let path = directory.join(name);
 
+        let projects = data::projects(icons);
+        let structured_data = projects
+            .iter()
+            .map(|project| jsonld::project(project, config))
+            .collect();
+
         let page = html!(
             main #projects {
+                h1 .visually-hidden { "Projects" }
                 div #ownprojects {
-                    h1 { "My Projects" }
+                    h2 { "My Projects" }
                     hr;
                     div .list {
-                        @for project in data::projects(input_path) {
+                        @for project in projects {
                             div .project {
-                                h1 .header { (project.title) }
+                                h3 .header { (project.title) }
                                 @if let Some(figure) = project.figure {
                                     @match figure {
                                         data::ProjectFigure::Icon(icon) => {
-                                            img src=(icon.output_path()) {}
+                                            (icons.markup(&*icon, ""))
                                         },
                                         data::ProjectFigure::Picture(path) => {
-                                            img src=(path) {}
+                                            img src=(path) alt="";
                                         }
                                     }
                                 }
@@ -322,12 +421,12 @@ impl Render for ProjectsPage {
 
                                 div .links {
                                     div {
-                                        img src=(icon!("Github", input_path).output_path()) {}
+                                        (icons.markup(&*icon!("Github", icons), ""))
                                         span { "View on " a href=(project.links.github) {"GitHub"}}
                                     }
                                     @if let Some(homepage) = project.links.homepage {
                                         div {
-                                            img src=(icon!("Info", input_path).output_path()) {}
+                                            (icons.markup(&*icon!("Info", icons), ""))
                                             span { "See " a href=(homepage) {"Project Page"}}
                                         }
                                     }
@@ -337,19 +436,19 @@ impl Render for ProjectsPage {
                     }
                 }
                 div #contributions {
-                    h1 { "Contributions" }
+                    h2 { "Contributions" }
                     hr;
                     div .list {
-                        @for project in data::contribution_projects(input_path) {
+                        @for project in data::contribution_projects(icons) {
                             div .project {
-                                h1 .header { (project.title) }
+                                h3 .header { (project.title) }
                                 @if let Some(figure) = project.figure {
                                     @match figure {
                                         data::ProjectFigure::Icon(icon) => {
-                                            img src=(icon.output_path()) {}
+                                            (icons.markup(&*icon, ""))
                                         },
                                         data::ProjectFigure::Picture(path) => {
-                                            img src=(path) {}
+                                            img src=(path) alt="";
                                         }
                                     }
                                 }
@@ -383,12 +482,12 @@ impl Render for ProjectsPage {
 
                                 div .links {
                                     div {
-                                        img src=(icon!("Github", input_path).output_path()) {}
+                                        (icons.markup(&*icon!("Github", icons), ""))
                                         span { "View on " a href=(project.links.github) {"GitHub"}}
                                     }
                                     @if let Some(homepage) = project.links.homepage {
                                         div {
-                                            img src=(icon!("Info", input_path).output_path()) {}
+                                            (icons.markup(&*icon!("Info", icons), ""))
                                             span { "See " a href=(homepage) {"Project Page"}}
                                         }
                                     }
@@ -400,9 +499,22 @@ impl Render for ProjectsPage {
             }
         );
 
-        render::render_into(frame(data::FULLNAME, page, input_path), &path);
+        let page_path = Path::new(dir).join(name).to_str().unwrap().to_owned();
+        renderer.render_into(
+            frame(
+                &Metadata {
+                    structured_data,
+                    history: history.page(Self::SOURCES),
+                    ..Metadata::page(&config.title, &page_path, config)
+                },
+                page,
+                config,
+                icons,
+            ),
+            &path,
+        );
         vec![Page {
-            path: Path::new(dir).join(name).to_str().unwrap().to_owned(),
+            path: page_path,
             filetype: FileType::Html,
         }]
     }
@@ -411,7 +523,18 @@ impl Render for ProjectsPage {
 pub struct AboutPage;
 
 impl Render for AboutPage {
-    fn render(output_base_path: &Path, input_path: &Path) -> Vec<Page> {
+    const SOURCES: &'static [Source<'static>] = &[Source::Lines {
+        file: "generator/src/pages.rs",
+        range: "^/^impl Render for AboutPage/,/^}/",
+    }];
+
+    fn render(
+        output_base_path: &Path,
+        renderer: &Renderer,
+        config: &SiteConfig,
+        icons: &IconRegistry,
+        history: &History,
+    ) -> Vec<Page> {
         let (dir, name) = ("about", "index.html");
 
         let directory = output_base_path.join(dir);
@@ -428,7 +551,7 @@ impl Render for AboutPage {
                         "I'm Hannes Körber, a technology enthusiast currently living in Ansbach, Germany."
                     }
 
-                    h1 { "Why I do what I am doing" }
+                    h2 { "Why I do what I am doing" }
 
                     p { "I started working with computers when I was around ten years old. In the beginning, I mainly used them for gaming, but got more and more interested in the internals --- how a computer actually works."}
 
@@ -436,7 +559,7 @@ impl Render for AboutPage {
 
                     p { "During my university years, I first came in contact with Linux. It was like discovering computers all over again. With Linux, I was free to do everything I wanted with my computer. A few months after having my first contact with Linux, I abandoned Windows for good and have not looked back. I quickly learned everything I could about Linux and computer science in general. By choosing computer science courses over Electrical engineering courses (which I still like and do as a hobby) I decided on my career path: Information Technology."}
 
-                    h1 { "What I do in my free time" }
+                    h2 { "What I do in my free time" }
 
                     p {"I once read somewhere that you should have one hobby in each of the following three categories:"}
 
@@ -449,16 +572,16 @@ impl Render for AboutPage {
                     p {"Well, the last one for me is the one that comes most naturally: I take care of my own private "cloud" that encompasses a few services that for me replace google, dropbox etc. I use this to keep up to date on a lot of technologies that I cannot work with in my day-to-day job. So it indirectly makes me money by increasing my market value."}
 
 
-                    h2 { "Sports"}
+                    h3 { "Sports"}
 
                     div .with-pictures .pictures-right {
                         div .pictures {
                             figure {
-                                img width="200px" src="/assets/images/nebelhorn.jpg" {}
+                                img width="200px" src="/assets/images/nebelhorn.jpg" alt="On top of the Nebelhorn in winter";
                                 figcaption {"Nebelhorn, Oberstdorf, February 2020"}
                             }
                             figure {
-                                img width="300px" src="/assets/images/kayak-naab.jpg" {}
+                                img width="300px" src="/assets/images/kayak-naab.jpg" alt="Kayaking on the Naab";
                                 figcaption {"Naab, Schwandorf, September 2020"}
                             }
                         }
@@ -494,12 +617,12 @@ impl Render for AboutPage {
                         }
                     }
 
-                    h2 {"Creativity"}
+                    h3 {"Creativity"}
 
                     div .with-pictures .pictures-right {
                         div .pictures {
                             figure {
-                                img width="300px" src="/assets/images/guitar.jpg" {}
+                                img width="300px" src="/assets/images/guitar.jpg" alt="Playing the guitar";
                                 figcaption {"Amsterdam, July 2019"}
                             }
                         }
@@ -517,7 +640,7 @@ impl Render for AboutPage {
                     div .with-pictures .pictures-right {
                         div .pictures {
                             figure {
-                                img width="260px" src="/assets/images/yamaha-p45.jpg" {}
+                                img width="260px" src="/assets/images/yamaha-p45.jpg" alt="Yamaha P-45 electric piano";
                                 figcaption {"Yamaha P-45"}
                             }
                         }
@@ -539,7 +662,7 @@ impl Render for AboutPage {
                     div .with-pictures .pictures-right {
                         div .pictures {
                             figure {
-                                img width="200px" src="/assets/images/chess.jpg" {}
+                                img width="200px" src="/assets/images/chess.jpg" alt="A chess board";
                             }
                         }
 
@@ -562,9 +685,147 @@ impl Render for AboutPage {
             }
         );
 
-        render::render_into(frame(data::FULLNAME, page, input_path), &path);
+        let page_path = Path::new(dir).join(name).to_str().unwrap().to_owned();
+        renderer.render_into(
+            frame(
+                &Metadata {
+                    structured_data: vec![jsonld::person(config)],
+                    history: history.page(Self::SOURCES),
+                    ..Metadata::page(&config.title, &page_path, config)
+                },
+                page,
+                config,
+                icons,
+            ),
+            &path,
+        );
+        vec![Page {
+            path: page_path,
+            filetype: FileType::Html,
+        }]
+    }
+}
+
+pub struct SearchPage;
+
+impl Render for SearchPage {
+    const SOURCES: &'static [Source<'static>] = &[Source::Lines {
+        file: "generator/src/pages.rs",
+        range: "^/^impl Render for SearchPage/,/^}/",
+    }];
+
+    fn render(
+        output_base_path: &Path,
+        renderer: &Renderer,
+        config: &SiteConfig,
+        icons: &IconRegistry,
+        history: &History,
+    ) -> Vec<Page> {
+        let (dir, name) = ("search", "index.html");
+
+        let directory = output_base_path.join(dir);
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let path = directory.join(name);
+
+        let input = html!(
+            input
+                #search-input
+                type="search"
+                name="q"
+                placeholder="Search blog posts"
+                aria-label="Search blog posts"
+                autocomplete="off";
+        );
+
+        let page = match config.search.mode {
+            SearchMode::Client => html!(
+                main #search {
+                    h1 { "Search" }
+                    form #search-form role="search" hx-boost="false" data-index=(format!("/{}", search::INDEX_PATH)) {
+                        (input)
+                    }
+                    noscript {
+                        p { "Searching requires JavaScript." }
+                    }
+                    p #search-status aria-live="polite" {}
+                    div .postlist {
+                        table {
+                            tbody #search-results {}
+                        }
+                    }
+                    template #search-result {
+                        tr {
+                            td {
+                                a href=(config.blog.link("index.html")) { "Post" }
+                            }
+                            td .date {}
+                        }
+                    }
+                    script { (PreEscaped(search::SCRIPT)) }
+                }
+            ),
+            SearchMode::Server => html!(
+                main #search {
+                    h1 { "Search" }
+                    form
+                        #search-form
+                        role="search"
+                        action="/search"
+                        hx-get="/search"
+                        hx-target="#search-results"
+                        hx-trigger="input changed delay:300ms from:#search-input, submit"
+                    {
+                        (input)
+                    }
+                    script { (PreEscaped(search::QUERY_SCRIPT)) }
+                    noscript {
+                        p { "Searching requires JavaScript." }
+                    }
+                    div
+                        #search-results
+                        aria-live="polite"
+                        hx-get="/search"
+                        hx-trigger="load"
+                        hx-include="#search-input"
+                    {}
+                    // the server responds with this markup, it is part of the
+                    // page so the styles for it are kept
+                    template #search-result {
+                        p .search-status { "1 post found" }
+                        div .postlist {
+                            table {
+                                tbody {
+                                    tr {
+                                        td {
+                                            a href=(config.blog.link("index.html")) { "Post" }
+                                            p .snippet { "… " mark { "term" } " …" }
+                                        }
+                                        td .date {}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            ),
+        };
+
+        let page_path = Path::new(dir).join(name).to_str().unwrap().to_owned();
+        renderer.render_into(
+            frame(
+                &Metadata {
+                    history: history.page(Self::SOURCES),
+                    ..Metadata::page("Search", &page_path, config)
+                },
+                page,
+                config,
+                icons,
+            ),
+            &path,
+        );
         vec![Page {
-            path: Path::new(dir).join(name).to_str().unwrap().to_owned(),
+            path: page_path,
             filetype: FileType::Html,
         }]
     }
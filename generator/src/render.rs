@@ -2,8 +2,60 @@ use maud::Markup;
 use std::fs;
 use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub fn render_into(output: Markup, path: &Path) {
-    let mut handle = fs::File::create(path).unwrap();
-    handle.write_all(output.into_string().as_bytes()).unwrap();
+use super::minify;
+
+/// Writes rendered pages to disk and keeps track of how much minification
+/// saved.
+pub struct Renderer {
+    minify: bool,
+    rendered_bytes: AtomicUsize,
+    written_bytes: AtomicUsize,
+}
+
+impl Renderer {
+    pub fn new(minify: bool) -> Self {
+        Self {
+            minify,
+            rendered_bytes: AtomicUsize::new(0),
+            written_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn render_into(&self, output: Markup, path: &Path) {
+        let rendered = output.into_string();
+        let output = if self.minify {
+            minify::html(&rendered)
+        } else {
+            rendered.clone()
+        };
+
+        self.rendered_bytes
+            .fetch_add(rendered.len(), Ordering::Relaxed);
+        self.written_bytes
+            .fetch_add(output.len(), Ordering::Relaxed);
+
+        let mut handle = fs::File::create(path).unwrap();
+        handle.write_all(output.as_bytes()).unwrap();
+    }
+
+    pub fn summary(&self) -> Option<String> {
+        if !self.minify {
+            return None;
+        }
+
+        let rendered = self.rendered_bytes.load(Ordering::Relaxed);
+        let written = self.written_bytes.load(Ordering::Relaxed);
+        let saved = rendered - written;
+
+        Some(format!(
+            "html minification: {rendered} -> {written} bytes, saved {saved} bytes ({:.1}%)",
+            if rendered == 0 {
+                0.0
+            } else {
+                saved as f64 * 100.0 / rendered as f64
+            }
+        ))
+    }
 }
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use comrak::nodes::{AstNode, NodeValue};
+use serde::Serialize;
+
+use super::{FileType, Page};
+
+/// Where the index is published, relative to the root of the site
+pub const INDEX_PATH: &str = "search/index.json";
+
+/// All posts in full, for the index of the server. Written next to the
+/// manifest, so it is not published.
+pub const DOCUMENTS_FILE: &str = "search-documents.json";
+
+/// How much a single occurrence of a term counts, depending on where it occurs.
+/// Written with the documents, so the server weighs its index the same way.
+#[derive(Serialize)]
+struct Weights {
+    title: u32,
+    tags: u32,
+    summary: u32,
+    body: u32,
+}
+
+const WEIGHTS: Weights = Weights {
+    title: 5,
+    tags: 3,
+    summary: 2,
+    body: 1,
+};
+
+/// Queries the index from the search page. Every query term has to match a
+/// term of the index exactly or as a prefix, documents are ranked by the sum
+/// of the weights of all matches, newer posts first on ties.
+pub const SCRIPT: &str = r#"
+(function () {
+  var form = document.getElementById("search-form");
+  var input = document.getElementById("search-input");
+  var status = document.getElementById("search-status");
+  var results = document.getElementById("search-results");
+  var template = document.getElementById("search-result");
+  var index = null;
+  var terms = [];
+
+  function tokenize(text) {
+    return text
+      .toLowerCase()
+      .split(/[^\p{L}\p{N}]+/u)
+      .filter(function (term) { return term.length > 1; });
+  }
+
+  function search(query) {
+    var queryTerms = tokenize(query);
+    var scores = new Map();
+
+    queryTerms.forEach(function (queryTerm) {
+      var matches = new Map();
+      terms.forEach(function (term) {
+        if (!term.startsWith(queryTerm)) {
+          return;
+        }
+        var factor = term === queryTerm ? 1 : 0.5;
+        index.terms[term].forEach(function (posting) {
+          var weight = posting[1] * factor;
+          matches.set(posting[0], Math.max(matches.get(posting[0]) || 0, weight));
+        });
+      });
+      matches.forEach(function (weight, id) {
+        var score = scores.get(id) || { weight: 0, terms: 0 };
+        scores.set(id, { weight: score.weight + weight, terms: score.terms + 1 });
+      });
+    });
+
+    return Array.from(scores)
+      .filter(function (entry) { return entry[1].terms === queryTerms.length; })
+      .map(function (entry) {
+        return { post: index.documents[entry[0]], weight: entry[1].weight };
+      })
+      .sort(function (a, b) {
+        return b.weight - a.weight || b.post.date.localeCompare(a.post.date);
+      })
+      .map(function (result) { return result.post; });
+  }
+
+  function show(query) {
+    results.replaceChildren();
+    if (!query.trim()) {
+      status.textContent = "";
+      return;
+    }
+
+    var posts = search(query);
+    status.textContent = posts.length + (posts.length === 1 ? " post" : " posts") + " found";
+
+    posts.forEach(function (post) {
+      var row = template.content.firstElementChild.cloneNode(true);
+      var link = row.querySelector("a");
+      link.href = post.url;
+      link.textContent = post.title;
+      link.title = post.summary;
+      row.querySelector(".date").textContent = post.date;
+      results.appendChild(row);
+    });
+  }
+
+  input.value = new URLSearchParams(window.location.search).get("q") || "";
+
+  fetch(form.dataset.index)
+    .then(function (response) { return response.json(); })
+    .then(function (data) {
+      index = data;
+      terms = Object.keys(index.terms);
+      show(input.value);
+    })
+    .catch(function () {
+      status.textContent = "The search index could not be loaded.";
+    });
+
+  input.addEventListener("input", function () {
+    var url = new URL(window.location.href);
+    if (input.value) {
+      url.searchParams.set("q", input.value);
+    } else {
+      url.searchParams.delete("q");
+    }
+    window.history.replaceState(null, "", url);
+    if (index) {
+      show(input.value);
+    }
+  });
+
+  form.addEventListener("submit", function (event) {
+    event.preventDefault();
+  });
+})();
+"#;
+
+/// Fills the search field of the server-side search page from the URL, the
+/// results for it are loaded once htmx processes the page.
+pub const QUERY_SCRIPT: &str = r#"
+document.getElementById("search-input").value =
+  new URLSearchParams(window.location.search).get("q") || "";
+"#;
+
+/// Splits text into lowercase terms. Has to match `tokenize()` in `SCRIPT`.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() > 1)
+        .map(str::to_lowercase)
+}
+
+/// The text content of a post, without code blocks.
+pub fn text<'a>(root: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+
+    for node in root.descendants() {
+        match &node.data.borrow().value {
+            NodeValue::Text(content) => text.push_str(content),
+            NodeValue::Code(code) => text.push_str(&code.literal),
+            NodeValue::SoftBreak
+            | NodeValue::LineBreak
+            | NodeValue::Paragraph
+            | NodeValue::Heading(_)
+            | NodeValue::Item(_)
+            | NodeValue::TableCell => text.push(' '),
+            _ => (),
+        }
+    }
+
+    text
+}
+
+#[derive(Serialize)]
+pub struct Document {
+    pub title: String,
+    pub url: String,
+    /// Plain text
+    pub summary: String,
+    pub date: String,
+}
+
+#[derive(Serialize)]
+struct Source<'a> {
+    #[serde(flatten)]
+    document: &'a Document,
+    tags: &'a [String],
+    body: &'a str,
+}
+
+#[derive(Serialize)]
+struct Sources<'a> {
+    weights: &'a Weights,
+    documents: Vec<Source<'a>>,
+}
+
+#[derive(Serialize, Default)]
+pub struct Index {
+    documents: Vec<Document>,
+    /// Maps every term to the documents it occurs in, together with its
+    /// weight in that document
+    terms: BTreeMap<String, Vec<(usize, u32)>>,
+    #[serde(skip)]
+    sources: Vec<(Vec<String>, String)>,
+}
+
+impl Index {
+    pub fn add(&mut self, document: Document, tags: &[&str], body: &str) {
+        let id = self.documents.len();
+        let mut weights: BTreeMap<String, u32> = BTreeMap::new();
+
+        let fields = [
+            (document.title.as_str(), WEIGHTS.title),
+            (document.summary.as_str(), WEIGHTS.summary),
+            (body, WEIGHTS.body),
+        ]
+        .into_iter()
+        .chain(tags.iter().map(|tag| (*tag, WEIGHTS.tags)));
+
+        for (text, weight) in fields {
+            for term in tokenize(text) {
+                *weights.entry(term).or_default() += weight;
+            }
+        }
+
+        for (term, weight) in weights {
+            self.terms.entry(term).or_default().push((id, weight));
+        }
+
+        self.documents.push(document);
+        self.sources.push((
+            tags.iter().map(|tag| tag.to_string()).collect(),
+            body.split_whitespace().collect::<Vec<&str>>().join(" "),
+        ));
+    }
+
+    pub fn write(&self, output_base_path: &Path) -> Page {
+        let path = output_base_path.join(INDEX_PATH);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, serde_json::to_string(self).unwrap()).unwrap();
+
+        Page {
+            path: INDEX_PATH.to_owned(),
+            filetype: FileType::Json,
+        }
+    }
+
+    /// Writes all documents including their text and the weights of their
+    /// fields, the server builds its own index from them.
+    pub fn write_documents(&self, output_base_path: &Path) -> PathBuf {
+        let documents: Vec<Source> = self
+            .documents
+            .iter()
+            .zip(&self.sources)
+            .map(|(document, (tags, body))| Source {
+                document,
+                tags,
+                body,
+            })
+            .collect();
+
+        std::fs::write(
+            output_base_path.join(DOCUMENTS_FILE),
+            serde_json::to_string(&Sources {
+                weights: &WEIGHTS,
+                documents,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        PathBuf::from(DOCUMENTS_FILE)
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashSet;
+
+use super::html::{self, Attribute, Token};
+
+/// Elements that only carry metadata and are not needed for rendering.
+const METADATA_ELEMENTS: &[&str] = &["metadata", "title", "desc"];
+
+/// Namespaces of editors like Inkscape, which store their state in the SVG.
+const EDITOR_NAMESPACES: &[&str] = &["inkscape", "sodipodi", "rdf", "dc", "cc", "sketch"];
+
+/// Attributes of the root element that only make sense for a standalone file.
+const DOCUMENT_ATTRIBUTES: &[&str] = &[
+    "xmlns",
+    "xmlns:xlink",
+    "version",
+    "width",
+    "height",
+    "viewBox",
+    "id",
+    "x",
+    "y",
+];
+
+fn is_editor_specific(name: &str) -> bool {
+    let name = name.strip_prefix("xmlns:").unwrap_or(name);
+    EDITOR_NAMESPACES
+        .iter()
+        .any(|namespace| name == *namespace || name.starts_with(&format!("{namespace}:")))
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Prefixes all references to the given names, i.e. `#name` for ids and
+/// `.name` for classes.
+fn prefix_references(
+    text: &str,
+    ids: &HashSet<String>,
+    classes: &HashSet<String>,
+    prefix: &str,
+) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        output.push(c);
+        let names = match c {
+            '#' => ids,
+            '.' => classes,
+            _ => continue,
+        };
+
+        let start = i + 1;
+        let mut end = start;
+        while let Some((j, c)) = chars.peek() {
+            if !is_identifier_char(*c) {
+                break;
+            }
+            end = j + c.len_utf8();
+            chars.next();
+        }
+
+        let name = &text[start..end];
+        if names.contains(name) {
+            output.push_str(&format!("{prefix}-"));
+        }
+        output.push_str(name);
+    }
+
+    output
+}
+
+pub struct Svg {
+    root_attributes: Vec<Attribute>,
+    content: String,
+}
+
+impl Svg {
+    /// Parses and minifies an SVG file. Comments, metadata and editor specific
+    /// markup are removed. All ids and classes are prefixed with `prefix`, so
+    /// multiple SVGs can be put into the same document without clashes.
+    pub fn parse(source: &str, prefix: &str) -> Result<Self, String> {
+        let tokens = html::tokenize(source);
+
+        let mut ids = HashSet::new();
+        let mut classes = HashSet::new();
+        for token in &tokens {
+            if let Token::StartTag(tag) = token {
+                if let Some(id) = tag.attribute("id") {
+                    ids.insert(id.to_owned());
+                }
+                if let Some(class) = tag.attribute("class") {
+                    classes.extend(class.split_whitespace().map(ToOwned::to_owned));
+                }
+            }
+        }
+
+        let mut root_attributes = None;
+        let mut content = String::new();
+        // depth of dropped elements we are currently in
+        let mut skipping = 0;
+        // depth below the root element
+        let mut depth = 0;
+        let mut in_style = false;
+
+        for token in tokens {
+            match token {
+                Token::StartTag(mut tag) => {
+                    if skipping > 0
+                        || METADATA_ELEMENTS.contains(&tag.name.as_str())
+                        || is_editor_specific(&tag.name)
+                    {
+                        if !tag.self_closing {
+                            skipping += 1;
+                        }
+                        continue;
+                    }
+
+                    tag.attributes
+                        .retain(|attribute| !is_editor_specific(&attribute.name));
+
+                    for attribute in &mut tag.attributes {
+                        let Some(value) = &attribute.value else {
+                            continue;
+                        };
+                        attribute.value = Some(match attribute.name.as_str() {
+                            "id" => format!("{prefix}-{value}"),
+                            "class" => value
+                                .split_whitespace()
+                                .map(|class| format!("{prefix}-{class}"))
+                                .collect::<Vec<String>>()
+                                .join(" "),
+                            _ => prefix_references(value, &ids, &HashSet::new(), prefix),
+                        });
+                    }
+
+                    if root_attributes.is_none() {
+                        if tag.name != "svg" {
+                            return Err(format!("unexpected root element {}", tag.name));
+                        }
+                        root_attributes = Some(tag.attributes);
+                        continue;
+                    }
+
+                    if !tag.self_closing {
+                        depth += 1;
+                    }
+                    in_style = tag.name == "style";
+                    content.push_str(&tag.serialize());
+                }
+                Token::EndTag(name) => {
+                    if skipping > 0 {
+                        skipping -= 1;
+                        continue;
+                    }
+                    if depth == 0 {
+                        // the end of the root element
+                        continue;
+                    }
+                    depth -= 1;
+                    in_style = false;
+                    content.push_str(&format!("</{name}>"));
+                }
+                Token::Text(text) => {
+                    let text = text.trim();
+                    if skipping > 0 || text.is_empty() {
+                        continue;
+                    }
+                    if in_style {
+                        content.push_str(&prefix_references(text, &ids, &classes, prefix));
+                    } else {
+                        content.push_str(text);
+                    }
+                }
+                Token::Declaration(_) | Token::Comment => (),
+            }
+        }
+
+        Ok(Self {
+            root_attributes: root_attributes.ok_or("no svg element found")?,
+            content,
+        })
+    }
+
+    fn root_attribute(&self, name: &str) -> Option<&str> {
+        self.root_attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .and_then(|attribute| attribute.value.as_deref())
+    }
+
+    /// Many icons only have a size, but scaling them requires a view box.
+    fn view_box(&self) -> Option<String> {
+        if let Some(view_box) = self.root_attribute("viewBox") {
+            return Some(view_box.to_owned());
+        }
+
+        let length = |name| {
+            self.root_attribute(name)
+                .map(|value| value.trim_end_matches("px"))
+                .and_then(|value| value.parse::<f64>().ok())
+        };
+
+        Some(format!("0 0 {} {}", length("width")?, length("height")?))
+    }
+
+    /// Root attributes that affect rendering, e.g. `fill`, and need to be kept
+    /// when the content is moved into another element.
+    fn presentation_attributes(&self) -> String {
+        self.root_attributes
+            .iter()
+            .filter(|attribute| !DOCUMENT_ATTRIBUTES.contains(&attribute.name.as_str()))
+            .map(|attribute| {
+                format!(
+                    " {}=\"{}\"",
+                    attribute.name,
+                    html::escape_attribute(attribute.value.as_deref().unwrap_or_default())
+                )
+            })
+            .collect()
+    }
+
+    fn view_box_attribute(&self) -> String {
+        self.view_box()
+            .map(|view_box| format!(" viewBox=\"{view_box}\""))
+            .unwrap_or_default()
+    }
+
+    /// A standalone SVG file.
+    pub fn to_file(&self) -> String {
+        let root = html::Tag {
+            name: "svg".into(),
+            attributes: self.root_attributes.clone(),
+            self_closing: false,
+        };
+        format!("{}{}</svg>", root.serialize(), self.content)
+    }
+
+    /// A `symbol` element for a sprite sheet.
+    pub fn to_symbol(&self, id: &str) -> String {
+        format!(
+            "<symbol id=\"{id}\"{}{}>{}</symbol>",
+            self.view_box_attribute(),
+            self.presentation_attributes(),
+            self.content
+        )
+    }
+
+    /// An `svg` element to be put directly into an HTML page. An empty `label`
+    /// marks the icon as decorative.
+    pub fn to_inline(&self, label: &str) -> String {
+        format!(
+            "<svg class=\"icon\"{}{}{}>{}</svg>",
+            accessibility_attributes(label),
+            self.view_box_attribute(),
+            self.presentation_attributes(),
+            self.content
+        )
+    }
+}
+
+pub fn accessibility_attributes(label: &str) -> String {
+    if label.is_empty() {
+        " aria-hidden=\"true\"".into()
+    } else {
+        format!(
+            " role=\"img\" aria-label=\"{}\"",
+            html::escape_attribute(label)
+        )
+    }
+}
+
+pub fn sprite_sheet(symbols: &[String]) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">{}</svg>",
+        symbols.concat()
+    )
+}
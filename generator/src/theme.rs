@@ -0,0 +1,113 @@
+use super::config::{Colors, ThemeConfig};
+
+/// All color tokens a palette has to define. They are available in the
+/// stylesheets as `var(--color-<token>)`.
+const TOKENS: &[&str] = &[
+    "background",
+    "text",
+    "text-muted",
+    "text-subtle",
+    "border",
+    "border-strong",
+    "surface",
+    "surface-alt",
+    "highlight",
+    "highlight-border",
+    "accent",
+    "tag-key",
+    "tag-key-text",
+    "tag-language",
+    "tag-language-text",
+    "tag-tech",
+    "tag-tech-text",
+    "tint-green",
+    "tint-red",
+    "tint-blue",
+    "tint-yellow",
+];
+
+/// Applies the theme stored in localStorage before the page is rendered, to
+/// prevent flashing the wrong colors. Without a stored theme, the stylesheet
+/// follows `prefers-color-scheme`.
+pub const SCRIPT: &str = r#"
+(function () {
+  try {
+    var theme = localStorage.getItem("theme");
+    if (theme === "light" || theme === "dark") {
+      document.documentElement.dataset.theme = theme;
+    }
+  } catch (e) {}
+})();
+
+function toggleTheme() {
+  var root = document.documentElement;
+  var dark = root.dataset.theme
+    ? root.dataset.theme === "dark"
+    : window.matchMedia("(prefers-color-scheme: dark)").matches;
+  root.dataset.theme = dark ? "light" : "dark";
+  try {
+    localStorage.setItem("theme", root.dataset.theme);
+  } catch (e) {}
+}
+"#;
+
+fn declarations(colors: &Colors, variant: &str) -> Result<String, String> {
+    let mut errors = vec![];
+
+    for token in TOKENS {
+        if !colors.contains_key(*token) {
+            errors.push(format!("{variant}: missing color {token}"));
+        }
+    }
+
+    for (token, value) in colors {
+        if !TOKENS.contains(&token.as_str()) {
+            errors.push(format!("{variant}: unknown color {token}"));
+        }
+        if value.contains([';', '{', '}']) {
+            errors.push(format!(
+                "{variant}: invalid value {value:?} for color {token}"
+            ));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+
+    Ok(colors
+        .iter()
+        .map(|(token, value)| format!("  --color-{token}: {value};\n"))
+        .collect())
+}
+
+fn try_stylesheet(theme: &ThemeConfig) -> Result<String, String> {
+    let palette = theme.palettes.get(&theme.palette).ok_or_else(|| {
+        format!(
+            "unknown palette {:?}, available: {}",
+            theme.palette,
+            theme
+                .palettes
+                .keys()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    })?;
+
+    let light = declarations(&palette.light, "light")?;
+    let dark = declarations(&palette.dark, "dark")?;
+
+    Ok(format!(
+        ":root {{\n  color-scheme: light;\n{light}}}\n\n\
+         :root[data-theme=dark] {{\n  color-scheme: dark;\n{dark}}}\n\n\
+         @media (prefers-color-scheme: dark) {{\n\
+         :root:not([data-theme=light]) {{\n  color-scheme: dark;\n{dark}}}\n}}\n"
+    ))
+}
+
+/// The custom properties of the configured palette.
+pub fn stylesheet(theme: &ThemeConfig) -> String {
+    try_stylesheet(theme)
+        .unwrap_or_else(|e| panic!("invalid theme palette {:?}:\n{e}", theme.palette))
+}
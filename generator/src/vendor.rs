@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use base64::Engine;
+use sha2::{Digest, Sha384};
+
+use super::config::Script;
+use super::{CheckMode, FileType, Page};
+
+pub const DIRECTORY: &str = "static/vendor";
+
+/// The Subresource Integrity hash of `content`.
+fn integrity(content: &[u8]) -> String {
+    format!(
+        "sha384-{}",
+        base64::engine::general_purpose::STANDARD.encode(Sha384::digest(content))
+    )
+}
+
+fn verify(name: &str, script: &Script, content: &[u8]) -> Result<(), String> {
+    let actual = integrity(content);
+    if actual != script.integrity {
+        return Err(format!(
+            "integrity mismatch for script {name} ({}): expected {}, got {actual}",
+            script.url, script.integrity
+        ));
+    }
+    Ok(())
+}
+
+/// Downloads all pinned scripts that are missing from the vendor directory or
+/// do not match their integrity hash.
+pub fn fetch(scripts: &BTreeMap<String, Script>, input_path: &Path) {
+    let directory = input_path.join(DIRECTORY);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    for (name, script) in scripts {
+        let path = directory.join(&script.file);
+
+        if let Ok(content) = std::fs::read(&path) {
+            if verify(name, script, &content).is_ok() {
+                println!("{name}: up to date");
+                continue;
+            }
+        }
+
+        let mut content = vec![];
+        ureq::get(&script.url)
+            .call()
+            .unwrap_or_else(|e| panic!("failed to download script {name} from {}: {e}", script.url))
+            .into_reader()
+            .read_to_end(&mut content)
+            .unwrap();
+
+        verify(name, script, &content).unwrap_or_else(|e| panic!("{e}"));
+
+        std::fs::write(&path, content).unwrap();
+        println!("{name}: downloaded {}", script.url);
+    }
+}
+
+/// Disables the enabled scripts that are missing from the vendor directory,
+/// so pages do not reference them. Pages only use scripts as progressive
+/// enhancement and still work without them.
+pub fn check_vendored(
+    scripts: &mut BTreeMap<String, Script>,
+    input_path: &Path,
+    check_mode: &CheckMode,
+) {
+    for (name, script) in scripts.iter_mut().filter(|(_name, script)| script.enabled) {
+        let local_path = input_path.join(DIRECTORY).join(&script.file);
+        if !local_path.is_file() {
+            check_mode.report(format!(
+                "script {name} is not vendored at {local_path:?}, leaving it out. Run `generator vendor` to download it"
+            ));
+            script.enabled = false;
+        }
+    }
+}
+
+/// Copies all enabled scripts into the output, after verifying that the
+/// vendored files still match their pinned hashes.
+pub fn copy_all(
+    scripts: &BTreeMap<String, Script>,
+    input_path: &Path,
+    output_base_path: &Path,
+) -> Vec<Page> {
+    std::fs::create_dir_all(output_base_path.join("vendor")).unwrap();
+
+    scripts
+        .iter()
+        .filter(|(_name, script)| script.enabled)
+        .map(|(name, script)| {
+            let local_path = input_path.join(DIRECTORY).join(&script.file);
+            let content = std::fs::read(&local_path)
+                .unwrap_or_else(|e| panic!("script {name} at {local_path:?} cannot be read: {e}"));
+            verify(name, script, &content).unwrap_or_else(|e| panic!("{e}"));
+
+            let path = script.output_path().trim_start_matches('/').to_owned();
+            std::fs::write(output_base_path.join(&path), content).unwrap();
+
+            Page {
+                path,
+                filetype: FileType::Js,
+            }
+        })
+        .collect()
+}
@@ -1,8 +1,12 @@
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::{fs, process};
 
+#[path = "src/text.rs"]
+mod text;
+
 #[derive(Debug, Deserialize)]
 enum FileType {
     Html,
@@ -10,6 +14,11 @@ enum FileType {
     Css,
     Jpg,
     Png,
+    Gif,
+    Webp,
+    Js,
+    Json,
+    Xml,
 }
 
 impl FileType {
@@ -20,6 +29,11 @@ impl FileType {
             Self::Css => "text/css;charset=utf-8",
             Self::Jpg => "image/jpeg",
             Self::Png => "image/png",
+            Self::Gif => "image/gif",
+            Self::Webp => "image/webp",
+            Self::Js => "text/javascript;charset=utf-8",
+            Self::Json => "application/json",
+            Self::Xml => "application/xml;charset=utf-8",
         }
     }
 }
@@ -40,6 +54,83 @@ impl Page {
 struct Manifest {
     content_directory: PathBuf,
     pages: Vec<Page>,
+    search_documents: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct Document {
+    title: String,
+    url: String,
+    summary: String,
+    date: String,
+    tags: Vec<String>,
+    body: String,
+}
+
+/// How much a single occurrence of a term counts, depending on where it occurs,
+/// as chosen by the generator
+#[derive(Debug, Deserialize)]
+struct Weights {
+    title: u32,
+    tags: u32,
+    summary: u32,
+    body: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchDocuments {
+    weights: Weights,
+    documents: Vec<Document>,
+}
+
+/// Writes all documents and an inverted index of their stemmed terms as static
+/// data. The terms are sorted, so they can be searched with a binary search.
+fn write_search_index(SearchDocuments { weights, documents }: &SearchDocuments) {
+    let mut terms: BTreeMap<String, BTreeMap<usize, u32>> = BTreeMap::new();
+
+    for (id, document) in documents.iter().enumerate() {
+        let fields = [
+            (document.title.as_str(), weights.title),
+            (document.summary.as_str(), weights.summary),
+            (document.body.as_str(), weights.body),
+        ]
+        .into_iter()
+        .chain(document.tags.iter().map(|tag| (tag.as_str(), weights.tags)));
+
+        for (text, weight) in fields {
+            for (_offset, word) in text::words(text) {
+                *terms
+                    .entry(text::stem(word))
+                    .or_default()
+                    .entry(id)
+                    .or_default() += weight;
+            }
+        }
+    }
+
+    let mut code = String::from("pub static DOCUMENTS: &[super::search::Document] = &[\n");
+    for document in documents {
+        code.push_str(&format!(
+            "super::search::Document {{ title: {:?}, url: {:?}, summary: {:?}, date: {:?}, body: {:?} }},\n",
+            document.title, document.url, document.summary, document.date, document.body,
+        ));
+    }
+    code.push_str("];\n\n");
+
+    code.push_str("pub static TERMS: &[(&str, &[(usize, u32)])] = &[\n");
+    for (term, postings) in &terms {
+        code.push_str(&format!(
+            "({term:?}, &{:?}),\n",
+            postings.iter().collect::<Vec<(&usize, &u32)>>()
+        ));
+    }
+    code.push_str("];\n");
+
+    fs::write(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("search_index.rs"),
+        code,
+    )
+    .unwrap();
 }
 
 fn write_router(build_directory: &Path, manifest: &Manifest) {
@@ -127,6 +218,12 @@ fn main() {
 
     write_router(build_directory.as_ref(), &manifest);
 
+    let documents: SearchDocuments = serde_json::from_str(
+        &fs::read_to_string(build_directory.join(&manifest.search_documents)).unwrap(),
+    )
+    .unwrap();
+    write_search_index(&documents);
+
     // this will always rebuild, as the manifest will be rewritten by the generator call above, so it will be changed
     // on every run. this could be optimized by checking the *content* of manifest (and writing checksums in there),
     // but rebuilding is fine
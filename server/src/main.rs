@@ -1,6 +1,7 @@
 use axum::{
-    http::{StatusCode, Uri},
-    response::Redirect,
+    extract::Query,
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
     routing::get,
     serve, Router,
 };
@@ -10,11 +11,17 @@ use signal_hook::{
     iterator::Signals,
 };
 
-use std::{env, net::IpAddr, process, thread};
+use std::{collections::HashMap, env, net::IpAddr, process, thread};
 
 #[path = "../router.rs"]
 mod router;
 
+#[path = "../search_index.rs"]
+mod search_index;
+
+mod search;
+mod text;
+
 #[macro_export]
 macro_rules! add_route {
     ($router:ident, $path:literal, $content_type:literal, $file:literal) => {{
@@ -34,6 +41,29 @@ macro_rules! add_route {
     }};
 }
 
+/// htmx requests get the results as a fragment, everything else is redirected
+/// to the search page, which then loads the results.
+async fn search(Query(params): Query<HashMap<String, String>>, headers: HeaderMap) -> Response {
+    let query = params.get("q").map(String::as_str).unwrap_or_default();
+
+    if !headers.contains_key("hx-request") {
+        return Redirect::to(&search::page_url(query)).into_response();
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/html;charset=utf-8".to_owned()),
+            // keeps the query when reloading the page
+            (
+                header::HeaderName::from_static("hx-replace-url"),
+                search::page_url(query),
+            ),
+        ],
+        search::fragment(query),
+    )
+        .into_response()
+}
+
 #[tokio::main]
 async fn main() {
     let mut signals = Signals::new(&[SIGINT, SIGTERM, SIGHUP, SIGQUIT]).unwrap();
@@ -50,6 +80,7 @@ async fn main() {
     let app = Router::new()
         .merge(router::router())
         .merge(Router::new().route("/", get(|| async { Redirect::temporary("/index.html") })))
+        .route("/search", get(search))
         .fallback(|uri: Uri| async move { (StatusCode::NOT_FOUND, format!("No route for {uri}")) });
 
     let listener = tokio::net::TcpListener::bind((IpAddr::from([0, 0, 0, 0]), port))
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use super::search_index::{DOCUMENTS, TERMS};
+use super::text;
+
+/// The search page of the generator
+const PAGE: &str = "/search/index.html";
+
+/// The maximum number of results that are returned
+const LIMIT: usize = 20;
+
+/// The number of words around the matches that are shown for each result
+const SNIPPET_WORDS: usize = 30;
+
+pub struct Document {
+    pub title: &'static str,
+    pub url: &'static str,
+    pub summary: &'static str,
+    pub date: &'static str,
+    pub body: &'static str,
+}
+
+struct Term {
+    stem: String,
+    /// The last term of a query may be incomplete while typing, so it also
+    /// matches as a prefix
+    prefix: bool,
+}
+
+impl Term {
+    fn matches(&self, stem: &str) -> bool {
+        stem == self.stem || (self.prefix && stem.starts_with(&self.stem))
+    }
+}
+
+fn parse(query: &str) -> Vec<Term> {
+    let words = text::words(query);
+    let complete = query.ends_with(|c: char| !c.is_alphanumeric());
+    let count = words.len();
+
+    words
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_offset, word))| Term {
+            stem: text::stem(word),
+            prefix: i + 1 == count && !complete,
+        })
+        .collect()
+}
+
+/// The weight of every document for a term, also considering prefix matches.
+/// Exact matches count more.
+fn postings(term: &Term) -> HashMap<usize, f64> {
+    let mut weights = HashMap::new();
+
+    let start = TERMS.partition_point(|(stem, _)| *stem < term.stem.as_str());
+    for (stem, postings) in &TERMS[start..] {
+        if !term.matches(stem) {
+            break;
+        }
+        let factor = if *stem == term.stem { 1.0 } else { 0.5 };
+        for (document, weight) in *postings {
+            let weight = f64::from(*weight) * factor;
+            let entry = weights.entry(*document).or_insert(0.0);
+            if weight > *entry {
+                *entry = weight;
+            }
+        }
+    }
+
+    weights
+}
+
+/// Documents that match all terms, ranked by the weight of the matches. Terms
+/// that occur in fewer documents count more.
+fn rank(terms: &[Term]) -> Vec<&'static Document> {
+    let mut scores: Option<HashMap<usize, f64>> = None;
+
+    for term in terms {
+        let postings = postings(term);
+        let rarity = (1.0 + DOCUMENTS.len() as f64 / (1.0 + postings.len() as f64)).ln();
+
+        scores = Some(match scores {
+            None => postings
+                .into_iter()
+                .map(|(document, weight)| (document, weight * rarity))
+                .collect(),
+            Some(scores) => scores
+                .into_iter()
+                .filter_map(|(document, score)| {
+                    postings
+                        .get(&document)
+                        .map(|weight| (document, score + weight * rarity))
+                })
+                .collect(),
+        });
+    }
+
+    let mut results: Vec<(usize, f64)> = scores.unwrap_or_default().into_iter().collect();
+    results.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .total_cmp(a_score)
+            .then(DOCUMENTS[*b].date.cmp(DOCUMENTS[*a].date))
+    });
+
+    results
+        .into_iter()
+        .take(LIMIT)
+        .map(|(document, _score)| &DOCUMENTS[document])
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The part of the body with the most matches, with all matches highlighted.
+/// Falls back to the summary if the terms only occur elsewhere.
+fn snippet(document: &Document, terms: &[Term]) -> String {
+    let words = text::words(document.body);
+    let matches: Vec<bool> = words
+        .iter()
+        .map(|(_offset, word)| {
+            let stem = text::stem(word);
+            terms.iter().any(|term| term.matches(&stem))
+        })
+        .collect();
+
+    let best = (0..words.len().saturating_sub(SNIPPET_WORDS - 1).max(1))
+        .map(|start| {
+            let end = (start + SNIPPET_WORDS).min(words.len());
+            (start, matches[start..end].iter().filter(|m| **m).count())
+        })
+        .max_by(|(a_start, a), (b_start, b)| a.cmp(b).then(b_start.cmp(a_start)));
+
+    let Some((start, _count)) = best.filter(|(_start, count)| *count > 0) else {
+        return escape(document.summary);
+    };
+
+    let end = (start + SNIPPET_WORDS).min(words.len());
+
+    let mut output = String::new();
+    if start > 0 {
+        output.push_str("… ");
+    }
+
+    let mut position = words[start].0;
+    for ((offset, word), matched) in words[start..end].iter().zip(&matches[start..end]) {
+        output.push_str(&escape(&document.body[position..*offset]));
+        if *matched {
+            output.push_str(&format!("<mark>{}</mark>", escape(word)));
+        } else {
+            output.push_str(&escape(word));
+        }
+        position = offset + word.len();
+    }
+
+    if end < words.len() {
+        output.push_str(" …");
+    }
+
+    output
+}
+
+/// The results for `query` as an HTML fragment, with the same markup as the
+/// template on the search page.
+pub fn fragment(query: &str) -> String {
+    let terms = parse(query);
+    if terms.is_empty() {
+        return String::new();
+    }
+
+    let results = rank(&terms);
+
+    let mut output = format!(
+        "<p class=\"search-status\">{} {} found</p>",
+        results.len(),
+        if results.len() == 1 { "post" } else { "posts" }
+    );
+
+    if results.is_empty() {
+        return output;
+    }
+
+    output.push_str("<div class=\"postlist\"><table><tbody>");
+    for document in results {
+        output.push_str(&format!(
+            "<tr><td><a href=\"{}\" title=\"{}\">{}</a><p class=\"snippet\">{}</p></td><td class=\"date\">{}</td></tr>",
+            escape(document.url),
+            escape(document.summary),
+            escape(document.title),
+            snippet(document, &terms),
+            escape(document.date),
+        ));
+    }
+    output.push_str("</tbody></table></div>");
+
+    output
+}
+
+/// The URL of the search page showing the results for `query`.
+pub fn page_url(query: &str) -> String {
+    let mut url = String::from(PAGE);
+    if !query.is_empty() {
+        url.push_str("?q=");
+        for byte in query.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                url.push(char::from(byte));
+            } else {
+                url.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    url
+}
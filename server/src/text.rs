@@ -0,0 +1,83 @@
+// Shared by the build script, which builds the search index, and the server,
+// which queries it. Both have to split and stem words the same way.
+
+/// All words of `text` that are longer than one character, together with
+/// their byte offsets.
+pub fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if text[s..i].chars().count() > 1 {
+                    words.push((s, &text[s..i]));
+                }
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    words
+}
+
+/// Suffixes that are removed after plurals, longest first.
+const SUFFIXES: &[&str] = &[
+    "ations", "ation", "ments", "ment", "ings", "ing", "edly", "ed", "ly",
+];
+
+fn has_vowel(word: &str) -> bool {
+    word.contains(['a', 'e', 'i', 'o', 'u', 'y'])
+}
+
+/// A very basic stemmer for English, so that e.g. "deploy", "deploys",
+/// "deploying" and "deployment" all match each other.
+pub fn stem(word: &str) -> String {
+    let mut word = word.to_lowercase();
+
+    if !word.is_ascii() || word.len() <= 3 {
+        return word;
+    }
+
+    if word.ends_with("sses") {
+        word.truncate(word.len() - 2);
+    } else if word.ends_with("ies") {
+        word.truncate(word.len() - 3);
+        word.push('y');
+    } else if word.ends_with('s')
+        && !word.ends_with("ss")
+        && !word.ends_with("us")
+        && !word.ends_with("is")
+    {
+        word.pop();
+    }
+
+    for suffix in SUFFIXES {
+        if let Some(base) = word.strip_suffix(suffix) {
+            if base.len() >= 3 && has_vowel(base) {
+                word.truncate(base.len());
+                // "running" -> "run", but not "install" -> "instal"
+                let bytes = word.as_bytes();
+                if bytes.len() > 3
+                    && bytes[bytes.len() - 1] == bytes[bytes.len() - 2]
+                    && !b"lsz".contains(&bytes[bytes.len() - 1])
+                {
+                    word.pop();
+                }
+            }
+            break;
+        }
+    }
+
+    // "make" and "making" -> "mak"
+    if word.len() > 3 && word.ends_with('e') {
+        word.pop();
+    }
+
+    word
+}